@@ -1,4 +1,4 @@
-use mm_nn::nn::{NNArch, NNMatrix, T};
+use mm_nn::nn::{Activation, Adam, NNArch, NNMatrixView, Optimizer, T};
 use std::env;
 
 fn main() {
@@ -12,6 +12,9 @@ fn main() {
         None => 10000,
     };
 
+    // seed for the initial weights, a random one when not given
+    let seed: Option<u64> = args.next().and_then(|value| value.parse::<u64>().ok());
+
     let _and_data_frame: Vec<T> = vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
     let _or_data_frame: Vec<T> = vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0];
     let _xor_data_frame: Vec<T> = vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
@@ -20,17 +23,21 @@ fn main() {
     let td = _xor_data_frame;
     // learning rate
     let rate: T = 1e-1;
-    // ε epsilon (limit that tends to 0)
-    let eps: T = 1e-1;
 
-    let df_input: NNMatrix = NNMatrix::new(Some(&td[..]), 4, 2, 3);
-    let df_output: NNMatrix = NNMatrix::new(Some(&td[2..]), 4, 1, 3);
+    // input and output share the training data buffer, nothing is copied
+    let df_input: NNMatrixView = NNMatrixView::new(&td[..], 4, 2, 3);
+    let df_output: NNMatrixView = NNMatrixView::new(&td[2..], 4, 1, 3);
 
     let layer_arch: Vec<usize> = vec![2, 2, 1];
+    let activations = [Activation::Sigmoid; 2];
 
-    let mut model = NNArch::create(&layer_arch[..]);
+    let mut model = NNArch::create(&layer_arch[..], &activations);
+    if let Some(seed) = seed {
+        model = model.with_seed(seed);
+    }
     model.randomize();
-    let mut gradient = NNArch::create(&layer_arch[..]);
+    let mut gradient = NNArch::create(&layer_arch[..], &activations);
+    let mut optimizer = Adam::new(rate);
 
     println!("input: {df_input}output: {df_output}");
     println!("model: {model}");
@@ -40,8 +47,8 @@ fn main() {
     );
 
     for _ in 0..iters {
-        model.finite_diff(&mut gradient, &df_input, &df_output, eps);
-        model.learn(&mut gradient, rate);
+        model.backprop(&mut gradient, &df_input, &df_output);
+        optimizer.step(&mut model, &gradient);
     }
 
     println!(
@@ -0,0 +1,79 @@
+use mm_nn::nn::{matmul_into, NNMatrix, T};
+use std::env;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// the i-j-k product NNMatrix used before matmul_into, allocating the result every call.
+fn naive(a: &NNMatrix, b: &NNMatrix) -> NNMatrix {
+    let mut c = NNMatrix::empty(a.rows, b.cols);
+    for i in 0..a.rows {
+        for j in 0..b.cols {
+            for k in 0..a.cols {
+                *c.get_mut_at(i, j) += a.get_at(i, k) * b.get_at(k, j);
+            }
+        }
+    }
+    c
+}
+
+/// average time of one call of f in microseconds.
+fn time<F: FnMut()>(iters: usize, mut f: F) -> f64 {
+    f();
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    start.elapsed().as_secs_f64() * 1e6 / iters as f64
+}
+
+fn main() {
+    let mut args = env::args();
+    let program_name: String = args.next().unwrap_or(String::from("no name found"));
+    println!("Running {program_name}");
+
+    // number of products timed per size
+    let iters: usize = match args.next() {
+        Some(value) => value.parse::<usize>().unwrap_or(100),
+        None => 100,
+    };
+
+    // (batch rows, inputs, outputs) of the layers of the 784-16-16-10 network in main.rs
+    let sizes = [
+        (1, 784, 16),
+        (32, 784, 16),
+        (256, 784, 16),
+        (256, 16, 16),
+        (256, 16, 10),
+    ];
+
+    println!("size            naive(us)  mul(us)  into(us)  speedup  max diff");
+    for (rows, inner, cols) in sizes {
+        let mut a = NNMatrix::empty(rows, inner);
+        let mut b = NNMatrix::empty(inner, cols);
+        a.rand_range(-1.0..1.0);
+        b.rand_range(-1.0..1.0);
+        let mut out = NNMatrix::empty(rows, cols);
+
+        let t_naive = time(iters, || {
+            black_box(naive(black_box(&a), black_box(&b)));
+        });
+        let t_mul = time(iters, || {
+            black_box(black_box(&a) * black_box(&b));
+        });
+        let t_into = time(iters, || {
+            matmul_into(black_box(&a), black_box(&b), &mut out.view_mut());
+        });
+
+        let expected = naive(&a, &b);
+        let diff = expected
+            .data_frame
+            .iter()
+            .zip(out.data_frame.iter())
+            .fold(0.0 as T, |acc, (x, y)| acc.max((x - y).abs()));
+        println!(
+            "{:<15} {t_naive:>9.2} {t_mul:>8.2} {t_into:>9.2} {:>7.1}x  {diff:e}",
+            format!("{rows}x{inner}x{cols}"),
+            t_naive / t_into
+        );
+    }
+}
@@ -1,6 +1,14 @@
-use mm_nn::nn::NNArch;
+use mm_nn::nn::{Activation, Initializer, Loss, NNArch};
 fn main() {
-    let mut a: NNArch = NNArch::create(&vec![28 * 28, 16, 16, 10][..]);
-    a.randomize_range(0.0..20.0);
+    let mut a: NNArch = NNArch::create(
+        &vec![28 * 28, 16, 16, 10][..],
+        &[
+            Activation::Sigmoid,
+            Activation::Sigmoid,
+            Activation::Softmax,
+        ],
+    )
+    .with_loss(Loss::CategoricalCrossEntropy);
+    a.initialize(Initializer::XavierUniform, Initializer::Zeros);
     print!("{a}");
 }
@@ -0,0 +1,178 @@
+use super::{sigmoid, Float, NNMatrix};
+use serde::{Deserialize, Serialize};
+
+/// sqrt(2 / pi), used by the tanh approximation of gelu.
+const GELU_C: f64 = 0.797_884_560_802_865_4;
+const GELU_K: f64 = 0.044_715;
+
+/// activation function applied to the output of a layer.
+/// every variant except Softmax works element by element, softmax normalizes each row.
+/// the parameters are f64 and rounded to the element type of the model when applied.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Activation {
+    Sigmoid,
+    Tanh,
+    Relu,
+    /// relu with a slope of alpha for negative input.
+    LeakyRelu(f64),
+    /// alpha * (e^z - 1) for negative input.
+    Elu(f64),
+    /// tanh approximation of the gaussian error linear unit.
+    Gelu,
+    Softplus,
+    Identity,
+    Softmax,
+}
+
+impl Activation {
+    /// value of the activation for a single pre-activation z.
+    /// panics for softmax, which depends on the whole row, use apply_row() for it.
+    pub fn apply<F: Float>(&self, z: F) -> F {
+        let (zero, one, half) = (F::ZERO, F::ONE, F::from_f64(0.5));
+        match *self {
+            Activation::Sigmoid => sigmoid(z),
+            Activation::Tanh => z.tanh(),
+            Activation::Relu => z.max(zero),
+            Activation::LeakyRelu(alpha) => {
+                if z > zero {
+                    z
+                } else {
+                    alpha.cast::<F>() * z
+                }
+            }
+            Activation::Elu(alpha) => {
+                if z > zero {
+                    z
+                } else {
+                    alpha.cast::<F>() * (z.exp() - one)
+                }
+            }
+            Activation::Gelu => {
+                let (c, k) = (F::from_f64(GELU_C), F::from_f64(GELU_K));
+                half * z * (one + (c * (z + k * z * z * z)).tanh())
+            }
+            Activation::Softplus => {
+                // log(1 + e^z) without overflowing for large z
+                z.max(zero) + (-z.abs()).exp().ln_1p()
+            }
+            Activation::Identity => z,
+            Activation::Softmax => panic!("softmax needs the whole row, use apply_row()"),
+        }
+    }
+
+    /// a = f(z) for one row, softmax normalizes the row so it sums to 1.
+    pub fn apply_row<F: Float>(&self, z: &[F], a: &mut [F]) {
+        assert!(z.len() == a.len());
+        if *self == Activation::Softmax {
+            // subtract the row max so exp never overflows
+            let max = z.iter().fold(F::NEG_INFINITY, |m, &v| m.max(v));
+            let mut sum = F::ZERO;
+            for (a, &z) in a.iter_mut().zip(z) {
+                *a = (z - max).exp();
+                sum += *a;
+            }
+            a.iter_mut().for_each(|a| *a /= sum);
+        } else {
+            for (a, &z) in a.iter_mut().zip(z) {
+                *a = self.apply(z);
+            }
+        }
+    }
+
+    /// da/dz for a single element given the pre-activation z and the activation a.
+    /// for softmax this is only the diagonal of the jacobian a * (1 - a).
+    pub fn derivative<F: Float>(&self, z: F, a: F) -> F {
+        let (zero, one, half) = (F::ZERO, F::ONE, F::from_f64(0.5));
+        match *self {
+            Activation::Sigmoid => a * (one - a),
+            Activation::Tanh => one - a * a,
+            Activation::Relu => {
+                if z > zero {
+                    one
+                } else {
+                    zero
+                }
+            }
+            Activation::LeakyRelu(alpha) => {
+                if z > zero {
+                    one
+                } else {
+                    alpha.cast()
+                }
+            }
+            Activation::Elu(alpha) => {
+                if z > zero {
+                    one
+                } else {
+                    a + alpha.cast()
+                }
+            }
+            Activation::Gelu => {
+                let (c, k) = (F::from_f64(GELU_C), F::from_f64(GELU_K));
+                let inner = c * (z + k * z * z * z);
+                let t = inner.tanh();
+                let dinner = c * (one + F::from_f64(3.0) * k * z * z);
+                half * (one + t) + half * z * (one - t * t) * dinner
+            }
+            Activation::Softplus => sigmoid(z),
+            Activation::Identity => one,
+            Activation::Softmax => a * (one - a),
+        }
+    }
+
+    /// a = f(z) for every element of z.
+    /// sigmoid and tanh use the slice kernels of F, the approximate simd ones for f32.
+    pub fn forward<F: Float>(&self, z: &NNMatrix<F>, a: &mut NNMatrix<F>) {
+        assert!(z.rows == a.rows && z.cols == a.cols);
+        match *self {
+            Activation::Sigmoid | Activation::Tanh => {
+                let kernel = if *self == Activation::Sigmoid {
+                    F::sigmoid_slice
+                } else {
+                    F::tanh_slice
+                };
+                let (z, mut a) = (z.view(), a.view_mut());
+                for i in 0..z.rows {
+                    let row = a.row_mut(i);
+                    row.copy_from_slice(z.row(i));
+                    kernel(row);
+                }
+            }
+            _ => {
+                for (a, z) in a.rows_mut().zip(z.rows()) {
+                    self.apply_row(z, a);
+                }
+            }
+        }
+    }
+
+    /// dz = da * f'(z), the gradient of the pre-activation from the gradient of the activation.
+    /// softmax uses the full jacobian: dz(j) = a(j) * (da(j) - sum(da(k) * a(k))).
+    pub fn backward<F: Float>(
+        &self,
+        z: &NNMatrix<F>,
+        a: &NNMatrix<F>,
+        da: &NNMatrix<F>,
+        dz: &mut NNMatrix<F>,
+    ) {
+        assert!(z.rows == a.rows && z.cols == a.cols);
+        assert!(da.rows == a.rows && da.cols == a.cols);
+        assert!(dz.rows == a.rows && dz.cols == a.cols);
+        match *self {
+            Activation::Softmax => {
+                for ((dz, a), da) in dz.rows_mut().zip(a.rows()).zip(da.rows()) {
+                    let dot: F = a.iter().zip(da).map(|(&a, &da)| a * da).sum();
+                    for ((dz, &a), &da) in dz.iter_mut().zip(a).zip(da) {
+                        *dz = a * (da - dot);
+                    }
+                }
+            }
+            _ => {
+                let values = z.iter().zip(a.iter()).zip(da.iter());
+                for (dz, ((&z, &a), &da)) in dz.iter_mut().zip(values) {
+                    *dz = da * self.derivative(z, a);
+                }
+            }
+        }
+    }
+}
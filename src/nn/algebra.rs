@@ -0,0 +1,591 @@
+use super::{
+    broadcast_shape, check_broadcast, check_shape, AsView, Float, MatrixError, NNMatrix,
+    NNMatrixView, NNMatrixViewMut,
+};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// ====================== element wise start ==================================== //
+// the element wise ops broadcast like numpy: a dimension of 1 on one side is repeated to the
+// size of the other side, so a 1 x cols row applies to every row, a rows x 1 column to every
+// column and a 1 x 1 matrix to every element.
+
+impl<'a, F: Float> NNMatrixView<'a, F> {
+    /// copy of self with its rows and columns of size 1 repeated to shape, which has to be a
+    /// broadcast shape of self.
+    pub(super) fn expand(&self, shape: (usize, usize)) -> NNMatrix<F> {
+        let mut c = NNMatrix::empty(shape.0, shape.1);
+        let mut out = c.view_mut();
+        for i in 0..shape.0 {
+            let row = self.row(if self.rows == 1 { 0 } else { i });
+            if self.cols == shape.1 {
+                out.row_mut(i).copy_from_slice(row);
+            } else {
+                out.row_mut(i).fill(row[0]);
+            }
+        }
+        c
+    }
+
+    /// f(a, b) of every pair of elements after broadcasting self and b to the same shape.
+    fn zip_with(
+        &self,
+        op: &'static str,
+        b: &NNMatrixView<F>,
+        f: impl FnMut(F, F) -> F,
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        let shape = broadcast_shape(op, (self.rows, self.cols), (b.rows, b.cols))?;
+        let mut c = self.expand(shape);
+        c.view_mut().zip_apply(op, b, f)?;
+        Ok(c)
+    }
+
+    /// element wise self - b, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_sub(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        self.zip_with("sub", b, |x, y| x - y)
+    }
+
+    /// element wise self / b, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_div(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        self.zip_with("div", b, |x, y| x / y)
+    }
+
+    /// element wise product, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_hadamard(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        self.zip_with("hadamard", b, |x, y| x * y)
+    }
+
+    pub fn hadamard(&self, b: &NNMatrixView<F>) -> NNMatrix<F> {
+        self.try_hadamard(b).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// f(a, b) of every pair of elements, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_zip_map(
+        &self,
+        b: &NNMatrixView<F>,
+        f: impl FnMut(F, F) -> F,
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.zip_with("zip_map", b, f)
+    }
+
+    pub fn zip_map(&self, b: &NNMatrixView<F>, f: impl FnMut(F, F) -> F) -> NNMatrix<F> {
+        self.try_zip_map(b, f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// f(x) of every element.
+    pub fn map(&self, mut f: impl FnMut(F) -> F) -> NNMatrix<F> {
+        let mut c = self.to_matrix();
+        c.view_mut()
+            .for_each_slice(|s| s.iter_mut().for_each(|v| *v = f(*v)));
+        c
+    }
+
+    /// every element limited to lo..=hi.
+    pub fn clamp(&self, lo: F, hi: F) -> NNMatrix<F> {
+        self.map(|v| v.clamp(lo, hi))
+    }
+}
+
+impl<F: Float> NNMatrixViewMut<'_, F> {
+    /// x = f(x, y) for every pair of elements, b is broadcast to the shape of self.
+    fn zip_apply(
+        &mut self,
+        op: &'static str,
+        b: &NNMatrixView<F>,
+        mut f: impl FnMut(F, F) -> F,
+    ) -> Result<(), MatrixError> {
+        check_broadcast(op, (self.rows, self.cols), (b.rows, b.cols))?;
+        for i in 0..self.rows {
+            let row = b.row(if b.rows == 1 { 0 } else { i });
+            if b.cols == self.cols {
+                for (x, &y) in self.row_mut(i).iter_mut().zip(row) {
+                    *x = f(*x, y);
+                }
+            } else {
+                self.row_mut(i).iter_mut().for_each(|x| *x = f(*x, row[0]));
+            }
+        }
+        Ok(())
+    }
+
+    /// element wise self -= b, b is broadcast to the shape of self.
+    pub fn try_sub_assign(&mut self, b: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        self.zip_apply("sub_assign", b, |x, y| x - y)
+    }
+
+    /// element wise self /= b, b is broadcast to the shape of self.
+    pub fn try_div_assign(&mut self, b: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        self.zip_apply("div_assign", b, |x, y| x / y)
+    }
+
+    /// element wise self *= b, b is broadcast to the shape of self.
+    pub fn try_hadamard_assign(&mut self, b: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        self.zip_apply("hadamard_assign", b, |x, y| x * y)
+    }
+
+    /// x = f(x) for every element.
+    pub fn apply(&mut self, mut f: impl FnMut(F) -> F) {
+        self.for_each_slice(|s| s.iter_mut().for_each(|v| *v = f(*v)));
+    }
+
+    /// self = self * b, b needs to be square so the shape of self does not change.
+    pub fn try_matmul_assign(&mut self, b: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        check_shape("matmul_assign", (self.cols, self.cols), (b.rows, b.cols))?;
+        let c = self.view().try_matmul(b)?;
+        self.try_copy_from(&c.view())
+    }
+}
+// ====================== element wise end ==================================== //
+
+// ====================== linear algebra start ==================================== //
+impl<'a, F: Float> NNMatrixView<'a, F> {
+    /// transposed copy, cols x rows.
+    pub fn t(&self) -> NNMatrix<F> {
+        let mut c = NNMatrix::empty(self.cols, self.rows);
+        for i in 0..self.rows {
+            for (j, &v) in self.row(i).iter().enumerate() {
+                c.data_frame[j * self.rows + i] = v;
+            }
+        }
+        c
+    }
+
+    /// self * b^T without transposing b, b needs self.cols cols.
+    /// every element is the dot product of a row of self and a row of b.
+    pub fn try_matmul_transposed(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        check_shape("matmul_transposed", (b.rows, self.cols), (b.rows, b.cols))?;
+        let mut c = NNMatrix::empty(self.rows, b.rows);
+        for i in 0..self.rows {
+            let a = self.row(i);
+            for j in 0..b.rows {
+                c.data_frame[i * b.rows + j] = a.iter().zip(b.row(j)).map(|(&x, &y)| x * y).sum();
+            }
+        }
+        Ok(c)
+    }
+
+    pub fn matmul_transposed(&self, b: &NNMatrixView<F>) -> NNMatrix<F> {
+        self.try_matmul_transposed(b)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// outer product of the row vectors self and b, self.cols x b.cols.
+    pub fn try_outer(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        check_shape("outer", (1, self.cols), (self.rows, self.cols))?;
+        check_shape("outer", (1, b.cols), (b.rows, b.cols))?;
+        let mut c = NNMatrix::empty(self.cols, b.cols);
+        for (i, &x) in self.row(0).iter().enumerate() {
+            for (o, &y) in c.view_mut().row_mut(i).iter_mut().zip(b.row(0)) {
+                *o = x * y;
+            }
+        }
+        Ok(c)
+    }
+
+    pub fn outer(&self, b: &NNMatrixView<F>) -> NNMatrix<F> {
+        self.try_outer(b).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// square root of the sum of the squared elements.
+    pub fn norm(&self) -> F {
+        (0..self.rows)
+            .map(|i| self.row(i).iter().map(|&v| v * v).sum::<F>())
+            .sum::<F>()
+            .sqrt()
+    }
+}
+// ====================== linear algebra end ==================================== //
+
+// ====================== reductions start ==================================== //
+impl<'a, F: Float> NNMatrixView<'a, F> {
+    /// the rows added together, 1 x cols, eg. the bias gradient of a batch.
+    pub fn sum_rows(&self) -> NNMatrix<F> {
+        let mut c = NNMatrix::empty(1, self.cols);
+        for row in self.rows() {
+            F::add_slice(c.row_mut(0), row);
+        }
+        c
+    }
+
+    /// the columns added together, rows x 1.
+    pub fn sum_cols(&self) -> NNMatrix<F> {
+        let mut c = NNMatrix::empty(self.rows, 1);
+        for (c, row) in c.iter_mut().zip(self.rows()) {
+            *c = row.iter().copied().sum();
+        }
+        c
+    }
+
+    /// sum of every element.
+    pub fn sum(&self) -> F {
+        self.rows().map(|row| row.iter().copied().sum::<F>()).sum()
+    }
+
+    /// mean of every element, nan for an empty matrix.
+    pub fn mean(&self) -> F {
+        self.sum() / F::from_usize(self.rows * self.cols)
+    }
+
+    /// largest element, negative infinity for an empty matrix.
+    pub fn max(&self) -> F {
+        self.iter().fold(F::NEG_INFINITY, |m, &v| m.max(v))
+    }
+
+    /// (row, col) of the first largest element, none for an empty matrix.
+    pub fn argmax(&self) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), F)> = None;
+        for (index, &v) in self.enumerate_indexed() {
+            if best.is_none_or(|(_, max)| v > max) {
+                best = Some((index, v));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    /// column of the first largest element of every row, eg. the predicted classes.
+    pub fn argmax_rows(&self) -> Vec<usize> {
+        self.rows()
+            .map(|row| (0..row.len()).fold(0, |best, j| if row[j] > row[best] { j } else { best }))
+            .collect()
+    }
+}
+// ====================== reductions end ==================================== //
+
+// ====================== matrix methods start ==================================== //
+impl<F: Float> NNMatrix<F> {
+    /// element wise self - b, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_sub(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_sub(&b.as_view())
+    }
+
+    /// element wise self -= b, b is broadcast to the shape of self.
+    pub fn try_sub_assign(
+        &mut self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<(), MatrixError> {
+        self.view_mut().try_sub_assign(&b.as_view())
+    }
+
+    /// element wise self / b, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_div(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_div(&b.as_view())
+    }
+
+    /// element wise self /= b, b is broadcast to the shape of self.
+    pub fn try_div_assign(
+        &mut self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<(), MatrixError> {
+        self.view_mut().try_div_assign(&b.as_view())
+    }
+
+    /// element wise product, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_hadamard(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_hadamard(&b.as_view())
+    }
+
+    /// element wise product, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn hadamard(&self, b: &(impl AsView<Elem = F> + ?Sized)) -> NNMatrix<F> {
+        self.view().hadamard(&b.as_view())
+    }
+
+    /// element wise self *= b, b is broadcast to the shape of self.
+    pub fn try_hadamard_assign(
+        &mut self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<(), MatrixError> {
+        self.view_mut().try_hadamard_assign(&b.as_view())
+    }
+
+    /// element wise self *= b, b is broadcast to the shape of self.
+    pub fn hadamard_assign(&mut self, b: &(impl AsView<Elem = F> + ?Sized)) {
+        self.try_hadamard_assign(b)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// f(a, b) of every pair of elements, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_zip_map(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+        f: impl FnMut(F, F) -> F,
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_zip_map(&b.as_view(), f)
+    }
+
+    /// f(a, b) of every pair of elements, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn zip_map(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+        f: impl FnMut(F, F) -> F,
+    ) -> NNMatrix<F> {
+        self.view().zip_map(&b.as_view(), f)
+    }
+
+    /// f(x) of every element.
+    pub fn map(&self, f: impl FnMut(F) -> F) -> NNMatrix<F> {
+        self.view().map(f)
+    }
+
+    /// x = f(x) for every element.
+    pub fn apply(&mut self, f: impl FnMut(F) -> F) {
+        self.view_mut().apply(f);
+    }
+
+    /// every element limited to lo..=hi.
+    pub fn clamp(&self, lo: F, hi: F) -> NNMatrix<F> {
+        self.view().clamp(lo, hi)
+    }
+
+    /// transposed copy, cols x rows.
+    pub fn t(&self) -> NNMatrix<F> {
+        self.view().t()
+    }
+
+    /// self * b^T without transposing b, b needs self.cols cols.
+    pub fn try_matmul_transposed(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_matmul_transposed(&b.as_view())
+    }
+
+    /// self * b^T without transposing b, b needs self.cols cols.
+    pub fn matmul_transposed(&self, b: &(impl AsView<Elem = F> + ?Sized)) -> NNMatrix<F> {
+        self.view().matmul_transposed(&b.as_view())
+    }
+
+    /// outer product of the row vectors self and b, self.cols x b.cols.
+    pub fn try_outer(
+        &self,
+        b: &(impl AsView<Elem = F> + ?Sized),
+    ) -> Result<NNMatrix<F>, MatrixError> {
+        self.view().try_outer(&b.as_view())
+    }
+
+    /// outer product of the row vectors self and b, self.cols x b.cols.
+    pub fn outer(&self, b: &(impl AsView<Elem = F> + ?Sized)) -> NNMatrix<F> {
+        self.view().outer(&b.as_view())
+    }
+
+    /// frobenius norm, the square root of the sum of the squared elements.
+    pub fn norm(&self) -> F {
+        self.view().norm()
+    }
+
+    /// the rows added together, 1 x cols.
+    pub fn sum_rows(&self) -> NNMatrix<F> {
+        self.view().sum_rows()
+    }
+
+    /// the columns added together, rows x 1.
+    pub fn sum_cols(&self) -> NNMatrix<F> {
+        self.view().sum_cols()
+    }
+
+    /// sum of every element.
+    pub fn sum(&self) -> F {
+        self.view().sum()
+    }
+
+    /// mean of every element, nan for an empty matrix.
+    pub fn mean(&self) -> F {
+        self.view().mean()
+    }
+
+    /// largest element, negative infinity for an empty matrix.
+    pub fn max(&self) -> F {
+        self.view().max()
+    }
+
+    /// (row, col) of the first largest element, none for an empty matrix.
+    pub fn argmax(&self) -> Option<(usize, usize)> {
+        self.view().argmax()
+    }
+
+    /// column of the first largest element of every row.
+    pub fn argmax_rows(&self) -> Vec<usize> {
+        self.view().argmax_rows()
+    }
+}
+// ====================== matrix methods end ==================================== //
+
+// ====================== arithmetic ops start ==================================== //
+// every binary operator is implemented for the owned, borrowed and view operands on both
+// sides. Mul of two matrices is the dot product, Add, Sub and Div are element wise and
+// broadcast like hadamard().
+
+/// implements op for every pair of operands, f(lhs view, rhs view) computes the result.
+macro_rules! binary_op {
+    ($op:ident, $method:ident, $f:expr) => {
+        binary_op!(@impl $op, $method, $f, NNMatrix<F>, NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, NNMatrix<F>, &NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, NNMatrix<F>, NNMatrixView<'_, F>);
+        binary_op!(@impl $op, $method, $f, &NNMatrix<F>, NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, &NNMatrix<F>, &NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, &NNMatrix<F>, NNMatrixView<'_, F>);
+        binary_op!(@impl $op, $method, $f, NNMatrixView<'_, F>, NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, NNMatrixView<'_, F>, &NNMatrix<F>);
+        binary_op!(@impl $op, $method, $f, NNMatrixView<'_, F>, NNMatrixView<'_, F>);
+    };
+    (@impl $op:ident, $method:ident, $f:expr, $lhs:ty, $rhs:ty) => {
+        impl<F: Float> $op<$rhs> for $lhs {
+            type Output = NNMatrix<F>;
+            fn $method(self, b: $rhs) -> NNMatrix<F> {
+                let f: fn(NNMatrixView<F>, NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> =
+                    $f;
+                f(self.as_view(), b.as_view()).unwrap_or_else(|e| panic!("{e}"))
+            }
+        }
+    };
+}
+
+/// implements op with a scalar right hand side for every operand, f(copy, scalar) updates a
+/// copy of the left hand side.
+macro_rules! scalar_op {
+    ($op:ident, $method:ident, $f:expr) => {
+        scalar_op!(@impl $op, $method, $f, NNMatrix<F>);
+        scalar_op!(@impl $op, $method, $f, &NNMatrix<F>);
+        scalar_op!(@impl $op, $method, $f, NNMatrixView<'_, F>);
+    };
+    (@impl $op:ident, $method:ident, $f:expr, $lhs:ty) => {
+        impl<F: Float> $op<F> for $lhs {
+            type Output = NNMatrix<F>;
+            fn $method(self, b: F) -> NNMatrix<F> {
+                let mut c = self.as_view().to_matrix();
+                let f: fn(&mut NNMatrix<F>, F) = $f;
+                f(&mut c, b);
+                c
+            }
+        }
+    };
+}
+
+/// implements op assign for every right hand side on NNMatrix and NNMatrixViewMut,
+/// f(lhs view, rhs view) updates the left hand side.
+macro_rules! assign_op {
+    ($op:ident, $method:ident, $f:expr) => {
+        assign_op!(@impl $op, $method, $f, NNMatrix<F>);
+        assign_op!(@impl $op, $method, $f, &NNMatrix<F>);
+        assign_op!(@impl $op, $method, $f, NNMatrixView<'_, F>);
+    };
+    (@impl $op:ident, $method:ident, $f:expr, $rhs:ty) => {
+        impl<F: Float> $op<$rhs> for NNMatrix<F> {
+            fn $method(&mut self, b: $rhs) {
+                self.view_mut().$method(b.as_view());
+            }
+        }
+
+        impl<F: Float> $op<$rhs> for NNMatrixViewMut<'_, F> {
+            fn $method(&mut self, b: $rhs) {
+                let f: fn(&mut NNMatrixViewMut<F>, &NNMatrixView<F>) -> Result<(), MatrixError> =
+                    $f;
+                f(self, &b.as_view()).unwrap_or_else(|e| panic!("{e}"));
+            }
+        }
+    };
+}
+
+binary_op!(Add, add, |a, b| a.try_add(&b));
+binary_op!(Sub, sub, |a, b| a.try_sub(&b));
+binary_op!(Div, div, |a, b| a.try_div(&b));
+binary_op!(Mul, mul, |a, b| a.try_matmul(&b));
+
+scalar_op!(Add, add, |c, b| *c += b);
+scalar_op!(Sub, sub, |c, b| *c -= b);
+scalar_op!(Mul, mul, |c, b| *c *= b);
+scalar_op!(Div, div, |c, b| *c /= b);
+
+assign_op!(AddAssign, add_assign, |a, b| a.try_add_assign(b));
+assign_op!(SubAssign, sub_assign, |a, b| a.try_sub_assign(b));
+assign_op!(DivAssign, div_assign, |a, b| a.try_div_assign(b));
+assign_op!(MulAssign, mul_assign, |a, b| a.try_matmul_assign(b));
+
+/// scaler add assign
+impl<F: Float> AddAssign<F> for NNMatrix<F> {
+    fn add_assign(&mut self, b: F) {
+        self.view_mut().add_assign(b);
+    }
+}
+
+/// scaler sub assign
+impl<F: Float> SubAssign<F> for NNMatrix<F> {
+    fn sub_assign(&mut self, b: F) {
+        self.view_mut().sub_assign(b);
+    }
+}
+
+/// scaler product assign
+impl<F: Float> MulAssign<F> for NNMatrix<F> {
+    fn mul_assign(&mut self, b: F) {
+        self.view_mut().mul_assign(b);
+    }
+}
+
+/// scaler division assign
+impl<F: Float> DivAssign<F> for NNMatrix<F> {
+    fn div_assign(&mut self, b: F) {
+        self.view_mut().div_assign(b);
+    }
+}
+
+/// scaler add assign
+impl<F: Float> AddAssign<F> for NNMatrixViewMut<'_, F> {
+    fn add_assign(&mut self, b: F) {
+        self.for_each_slice(|s| F::add_scalar_slice(s, b));
+    }
+}
+
+/// scaler sub assign
+impl<F: Float> SubAssign<F> for NNMatrixViewMut<'_, F> {
+    fn sub_assign(&mut self, b: F) {
+        self.for_each_slice(|s| F::add_scalar_slice(s, -b));
+    }
+}
+
+/// scaler product assign
+impl<F: Float> MulAssign<F> for NNMatrixViewMut<'_, F> {
+    fn mul_assign(&mut self, b: F) {
+        self.for_each_slice(|s| F::mul_scalar_slice(s, b));
+    }
+}
+
+/// scaler division assign
+impl<F: Float> DivAssign<F> for NNMatrixViewMut<'_, F> {
+    fn div_assign(&mut self, b: F) {
+        self.apply(|v| v / b);
+    }
+}
+
+/// negated copy
+impl<F: Float> Neg for NNMatrix<F> {
+    type Output = NNMatrix<F>;
+    fn neg(mut self) -> NNMatrix<F> {
+        self.apply(|v| -v);
+        self
+    }
+}
+
+/// negated copy
+impl<F: Float> Neg for &NNMatrix<F> {
+    type Output = NNMatrix<F>;
+    fn neg(self) -> NNMatrix<F> {
+        self.map(|v| -v)
+    }
+}
+
+/// negated copy
+impl<F: Float> Neg for NNMatrixView<'_, F> {
+    type Output = NNMatrix<F>;
+    fn neg(self) -> NNMatrix<F> {
+        self.map(|v| -v)
+    }
+}
+// ====================== arithmetic ops end ==================================== //
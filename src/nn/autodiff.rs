@@ -0,0 +1,430 @@
+use super::{Activation, AsView, Float, Loss, NNArch, NNMatrix, T};
+use std::cell::{Ref, RefCell};
+use std::ops::{Add, Index, Mul, Neg, Sub};
+
+/// how a node was computed, the usizes are the ids of its inputs.
+#[derive(Debug, Clone)]
+enum Op<F: Float> {
+    Leaf,
+    /// a + b, either side broadcast to the shape of the other like NNMatrix add
+    Add(usize, usize),
+    /// a - b, broadcast like Add
+    Sub(usize, usize),
+    Neg(usize),
+    /// a * b elementwise, broadcast like Add
+    Hadamard(usize, usize),
+    Scale(usize, F),
+    Matmul(usize, usize),
+    Transpose(usize),
+    Activation(usize, Activation),
+    Sum(usize),
+    Mean(usize),
+    SumRows(usize),
+    /// mean loss over the rows, fused holds the pre-activation when the loss skips the output
+    /// activation like NNArch::cost does.
+    Loss {
+        input: usize,
+        expected: NNMatrix<F>,
+        loss: Loss,
+        fused: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Node<F: Float> {
+    value: NNMatrix<F>,
+    op: Op<F>,
+}
+
+/// records the matrix operations of a forward pass so backward() can work out the gradient of
+/// every var on it. nodes are only ever appended, use a new tape for every step.
+#[derive(Debug, Default)]
+pub struct Tape<F: Float = T> {
+    nodes: RefCell<Vec<Node<F>>>,
+}
+
+/// a matrix recorded on a tape, cheap to copy. the operators and methods record a new node
+/// with the result.
+pub struct Var<'t, F: Float = T> {
+    tape: &'t Tape<F>,
+    id: usize,
+}
+
+impl<F: Float> Clone for Var<'_, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: Float> Copy for Var<'_, F> {}
+
+/// gradient of a scalar var with respect to every var it depends on.
+#[derive(Debug, Clone)]
+pub struct Gradients<F: Float = T> {
+    grads: Vec<Option<NNMatrix<F>>>,
+}
+
+impl<F: Float> Tape<F> {
+    pub fn new() -> Self {
+        Tape {
+            nodes: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// record a leaf, eg. an input or a parameter.
+    pub fn var(&self, value: &(impl AsView<Elem = F> + ?Sized)) -> Var<'_, F> {
+        self.push(value.as_view().to_matrix(), Op::Leaf)
+    }
+
+    /// number of recorded nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    fn push(&self, value: NNMatrix<F>, op: Op<F>) -> Var<'_, F> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(Node { value, op });
+        Var {
+            tape: self,
+            id: nodes.len() - 1,
+        }
+    }
+
+    /// record the result of f on the values of a and b.
+    fn binary(
+        &self,
+        a: usize,
+        b: usize,
+        f: impl FnOnce(&NNMatrix<F>, &NNMatrix<F>) -> NNMatrix<F>,
+        op: Op<F>,
+    ) -> Var<'_, F> {
+        let value = {
+            let nodes = self.nodes.borrow();
+            f(&nodes[a].value, &nodes[b].value)
+        };
+        self.push(value, op)
+    }
+
+    fn unary(
+        &self,
+        a: usize,
+        f: impl FnOnce(&NNMatrix<F>) -> NNMatrix<F>,
+        op: Op<F>,
+    ) -> Var<'_, F> {
+        let value = f(&self.nodes.borrow()[a].value);
+        self.push(value, op)
+    }
+}
+
+impl<'t, F: Float> Var<'t, F> {
+    /// the value computed in the forward pass.
+    pub fn value(&self) -> Ref<'t, NNMatrix<F>> {
+        Ref::map(self.tape.nodes.borrow(), |nodes| &nodes[self.id].value)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        let value = self.value();
+        (value.rows, value.cols)
+    }
+
+    pub fn matmul(self, b: Var<'t, F>) -> Var<'t, F> {
+        self.tape
+            .binary(self.id, b.id, |a, b| a * b, Op::Matmul(self.id, b.id))
+    }
+
+    pub fn hadamard(self, b: Var<'t, F>) -> Var<'t, F> {
+        self.tape.binary(
+            self.id,
+            b.id,
+            |a, b| a.hadamard(b),
+            Op::Hadamard(self.id, b.id),
+        )
+    }
+
+    pub fn t(self) -> Var<'t, F> {
+        self.tape.unary(self.id, |a| a.t(), Op::Transpose(self.id))
+    }
+
+    /// the activation applied to every row, self is the pre-activation.
+    pub fn activation(self, activation: Activation) -> Var<'t, F> {
+        self.tape.unary(
+            self.id,
+            |z| {
+                let mut a = NNMatrix::empty(z.rows, z.cols);
+                activation.forward(z, &mut a);
+                a
+            },
+            Op::Activation(self.id, activation),
+        )
+    }
+
+    /// sum of every element, 1 x 1.
+    pub fn sum(self) -> Var<'t, F> {
+        self.tape.unary(
+            self.id,
+            |a| NNMatrix::new(Some(&[a.sum()]), 1, 1, 1),
+            Op::Sum(self.id),
+        )
+    }
+
+    /// mean of every element, 1 x 1.
+    pub fn mean(self) -> Var<'t, F> {
+        self.tape.unary(
+            self.id,
+            |a| NNMatrix::new(Some(&[a.mean()]), 1, 1, 1),
+            Op::Mean(self.id),
+        )
+    }
+
+    /// the rows added together, 1 x cols.
+    pub fn sum_rows(self) -> Var<'t, F> {
+        self.tape
+            .unary(self.id, |a| a.sum_rows(), Op::SumRows(self.id))
+    }
+
+    /// loss against expected averaged over the rows, 1 x 1, the same value as NNArch::cost.
+    /// when self is an activation the loss fuses_with() the gradient skips the activation.
+    pub fn loss(self, expected: &(impl AsView<Elem = F> + ?Sized), loss: Loss) -> Var<'t, F> {
+        let expected = expected.as_view().to_matrix();
+        let (value, fused) = {
+            let nodes = self.tape.nodes.borrow();
+            let predicted = &nodes[self.id].value;
+            assert!(
+                predicted.rows == expected.rows && predicted.cols == expected.cols,
+                "loss: expected a {}x{} matrix found {}x{}",
+                predicted.rows,
+                predicted.cols,
+                expected.rows,
+                expected.cols
+            );
+            let fused = match nodes[self.id].op {
+                Op::Activation(z, activation) if loss.fuses_with(activation) => Some(z),
+                _ => None,
+            };
+            let mut cost = F::ZERO;
+            for (i, y) in expected.rows().enumerate() {
+                cost += match fused {
+                    Some(z) => loss.fused_value(nodes[z].value.row(i), y),
+                    None => loss.value(predicted.row(i), y),
+                };
+            }
+            (cost / F::from_usize(expected.rows), fused)
+        };
+        let op = Op::Loss {
+            input: self.id,
+            expected,
+            loss,
+            fused,
+        };
+        self.tape.push(NNMatrix::new(Some(&[value]), 1, 1, 1), op)
+    }
+
+    /// gradient of self, which has to be 1 x 1, with respect to every var recorded before it.
+    pub fn backward(self) -> Gradients<F> {
+        let nodes = self.tape.nodes.borrow();
+        let value = &nodes[self.id].value;
+        assert!(
+            value.rows == 1 && value.cols == 1,
+            "backward: expected a 1x1 var found {}x{}",
+            value.rows,
+            value.cols
+        );
+        let mut grads: Vec<Option<NNMatrix<F>>> = vec![None; self.id + 1];
+        grads[self.id] = Some(NNMatrix::new(Some(&[F::ONE]), 1, 1, 1));
+
+        for id in (0..=self.id).rev() {
+            // inputs always come before the node, so their gradients are in lower
+            let (lower, upper) = grads.split_at_mut(id);
+            let Some(g) = &upper[0] else {
+                continue;
+            };
+            let value_of = |id: usize| &nodes[id].value;
+            match &nodes[id].op {
+                Op::Leaf => {}
+                &Op::Add(a, b) => {
+                    accumulate(lower, a, unbroadcast(g.clone(), value_of(a)));
+                    accumulate(lower, b, unbroadcast(g.clone(), value_of(b)));
+                }
+                &Op::Sub(a, b) => {
+                    accumulate(lower, a, unbroadcast(g.clone(), value_of(a)));
+                    accumulate(lower, b, unbroadcast(-g, value_of(b)));
+                }
+                &Op::Neg(a) => accumulate(lower, a, -g),
+                &Op::Hadamard(a, b) => {
+                    let (a_value, b_value) = (value_of(a), value_of(b));
+                    accumulate(lower, a, unbroadcast(g.hadamard(b_value), a_value));
+                    accumulate(lower, b, unbroadcast(g.hadamard(a_value), b_value));
+                }
+                &Op::Scale(a, s) => accumulate(lower, a, g * s),
+                &Op::Matmul(a, b) => {
+                    // c = a * b, da = dc * b^T and db = a^T * dc
+                    accumulate(lower, a, g.matmul_transposed(value_of(b)));
+                    accumulate(lower, b, &value_of(a).t() * g);
+                }
+                &Op::Transpose(a) => accumulate(lower, a, g.t()),
+                &Op::Activation(z, activation) => {
+                    let (z_value, a_value) = (value_of(z), value_of(id));
+                    let mut dz = NNMatrix::empty(z_value.rows, z_value.cols);
+                    activation.backward(z_value, a_value, g, &mut dz);
+                    accumulate(lower, z, dz);
+                }
+                &Op::Sum(a) => {
+                    let a_value = value_of(a);
+                    let mut da = NNMatrix::empty(a_value.rows, a_value.cols);
+                    da.fill(g[(0, 0)]);
+                    accumulate(lower, a, da);
+                }
+                &Op::Mean(a) => {
+                    let a_value = value_of(a);
+                    let mut da = NNMatrix::empty(a_value.rows, a_value.cols);
+                    da.fill(g[(0, 0)] / F::from_usize(a_value.rows * a_value.cols));
+                    accumulate(lower, a, da);
+                }
+                &Op::SumRows(a) => {
+                    let a_value = value_of(a);
+                    let mut da = NNMatrix::empty(a_value.rows, a_value.cols);
+                    da += g;
+                    accumulate(lower, a, da);
+                }
+                Op::Loss {
+                    input,
+                    expected,
+                    loss,
+                    fused,
+                } => {
+                    let scale = g[(0, 0)] / F::from_usize(expected.rows);
+                    let predicted = value_of(*input);
+                    let mut d = NNMatrix::empty(predicted.rows, predicted.cols);
+                    for (i, y) in expected.rows().enumerate() {
+                        if fused.is_some() {
+                            loss.fused_gradient(predicted.row(i), y, d.row_mut(i));
+                        } else {
+                            loss.gradient(predicted.row(i), y, d.row_mut(i));
+                        }
+                    }
+                    accumulate(lower, fused.unwrap_or(*input), d * scale);
+                }
+            }
+        }
+        Gradients { grads }
+    }
+}
+
+/// add g to the gradient of id.
+fn accumulate<F: Float>(grads: &mut [Option<NNMatrix<F>>], id: usize, g: NNMatrix<F>) {
+    match &mut grads[id] {
+        Some(sum) => *sum += &g,
+        slot => *slot = Some(g),
+    }
+}
+
+/// sum g over the rows or columns that were broadcast to reach its shape from the shape of to.
+fn unbroadcast<F: Float>(mut g: NNMatrix<F>, to: &NNMatrix<F>) -> NNMatrix<F> {
+    if g.rows != to.rows {
+        g = g.sum_rows();
+    }
+    if g.cols != to.cols {
+        g = g.sum_cols();
+    }
+    g
+}
+
+impl<F: Float> Gradients<F> {
+    /// gradient of var, none when the result does not depend on it.
+    pub fn get(&self, var: Var<F>) -> Option<&NNMatrix<F>> {
+        self.grads.get(var.id).and_then(|g| g.as_ref())
+    }
+}
+
+/// panics when the result does not depend on var.
+impl<F: Float> Index<Var<'_, F>> for Gradients<F> {
+    type Output = NNMatrix<F>;
+
+    fn index(&self, var: Var<F>) -> &NNMatrix<F> {
+        self.get(var)
+            .unwrap_or_else(|| panic!("no gradient for var {}", var.id))
+    }
+}
+
+// ====================== operators start ==================================== //
+impl<'t, F: Float> Add for Var<'t, F> {
+    type Output = Var<'t, F>;
+
+    fn add(self, b: Var<'t, F>) -> Var<'t, F> {
+        self.tape
+            .binary(self.id, b.id, |a, b| a + b, Op::Add(self.id, b.id))
+    }
+}
+
+impl<'t, F: Float> Sub for Var<'t, F> {
+    type Output = Var<'t, F>;
+
+    fn sub(self, b: Var<'t, F>) -> Var<'t, F> {
+        self.tape
+            .binary(self.id, b.id, |a, b| a - b, Op::Sub(self.id, b.id))
+    }
+}
+
+/// matmul, like Mul of two NNMatrix.
+impl<'t, F: Float> Mul for Var<'t, F> {
+    type Output = Var<'t, F>;
+
+    fn mul(self, b: Var<'t, F>) -> Var<'t, F> {
+        self.matmul(b)
+    }
+}
+
+impl<'t, F: Float> Mul<F> for Var<'t, F> {
+    type Output = Var<'t, F>;
+
+    fn mul(self, s: F) -> Var<'t, F> {
+        self.tape.unary(self.id, |a| a * s, Op::Scale(self.id, s))
+    }
+}
+
+impl<'t, F: Float> Neg for Var<'t, F> {
+    type Output = Var<'t, F>;
+
+    fn neg(self) -> Var<'t, F> {
+        self.tape.unary(self.id, |a| -a, Op::Neg(self.id))
+    }
+}
+// ====================== operators end ==================================== //
+
+impl<F: Float> NNArch<F> {
+    /// record forward_batch of input on tape, returns the weight and bias vars in the order
+    /// of params() and the output var.
+    pub fn record<'t>(
+        &self,
+        tape: &'t Tape<F>,
+        input: &(impl AsView<Elem = F> + ?Sized),
+    ) -> (Vec<Var<'t, F>>, Var<'t, F>) {
+        let wl: Vec<Var<F>> = self.wl.iter().map(|w| tape.var(w)).collect();
+        let bl: Vec<Var<F>> = self.bl.iter().map(|b| tape.var(b)).collect();
+        let mut a = tape.var(input);
+        for i in 0..self.layer_count {
+            a = (a * wl[i] + bl[i]).activation(self.activations[i]);
+        }
+        (wl.into_iter().chain(bl).collect(), a)
+    }
+
+    /// the gradient of cost() written into gradient like backprop(), but worked out by a tape
+    /// instead of the hand derived backward pass.
+    pub fn tape_backprop(
+        &self,
+        gradient: &mut NNArch<F>,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+    ) {
+        let tape = Tape::new();
+        let (params, output) = self.record(&tape, df_input);
+        let grads = output.loss(df_output, self.loss).backward();
+        for (m, &p) in gradient.params_mut().into_iter().zip(&params) {
+            m.clone_from(&grads[p]);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// error returned by the try_ functions of NNMatrix and NNArch instead of panicking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    /// the right hand side of op has the wrong shape, shapes are (rows, cols)
+    ShapeMismatch {
+        op: &'static str,
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    /// the shapes of op can not be broadcast, a dimension differs and neither side is 1
+    Broadcast {
+        op: &'static str,
+        lhs: (usize, usize),
+        rhs: (usize, usize),
+    },
+    /// row, col is outside of a rows x cols matrix
+    OutOfBounds {
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    },
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::ShapeMismatch {
+                op,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{op}: expected a {}x{} matrix found {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            MatrixError::Broadcast { op, lhs, rhs } => write!(
+                f,
+                "{op}: can not broadcast a {}x{} matrix with a {}x{} matrix",
+                lhs.0, lhs.1, rhs.0, rhs.1
+            ),
+            MatrixError::OutOfBounds {
+                row,
+                col,
+                rows,
+                cols,
+            } => write!(
+                f,
+                "index ({row}, {col}) is out of bounds of a {rows}x{cols} matrix"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
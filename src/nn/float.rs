@@ -0,0 +1,265 @@
+use super::simd;
+use half::{bf16, f16};
+use rand::Rng;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Range, RangeInclusive, Sub, SubAssign,
+};
+
+/// element type of NNMatrix and NNArch, implemented for f32 (the default T) and f64, and for
+/// the 16 bit f16 and bf16 which store a matrix in half the bytes and compute through f32.
+/// the math functions mirror the inherent ones of f32 and f64, the slice functions are the
+/// hot loops of the model and use the simd kernels for f32.
+pub trait Float:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + fmt::LowerExp
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const EPSILON: Self;
+    const NEG_INFINITY: Self;
+
+    /// nearest value to v, rounding when Self has less precision.
+    fn from_f64(v: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    /// n as a float, eg. to average over n samples.
+    fn from_usize(n: usize) -> Self {
+        Self::from_f64(n as f64)
+    }
+
+    /// convert to another precision.
+    fn cast<G: Float>(self) -> G {
+        G::from_f64(self.to_f64())
+    }
+
+    /// uniform sample in 0..1, drawn the same way as rng.gen() so seeded runs match.
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self;
+
+    /// uniform sample in range, drawn the same way as rng.gen_range().
+    fn sample_range<R: Rng + ?Sized>(rng: &mut R, range: Range<Self>) -> Self;
+
+    /// uniform sample in range including its end.
+    fn sample_range_inclusive<R: Rng + ?Sized>(rng: &mut R, range: RangeInclusive<Self>) -> Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn clamp(self, lo: Self, hi: Self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn ln_1p(self) -> Self;
+    fn tanh(self) -> Self;
+    fn cos(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+
+    /// x = sigmoid(x) for every element.
+    fn sigmoid_slice(x: &mut [Self]) {
+        x.iter_mut()
+            .for_each(|v| *v = Self::ONE / (Self::ONE + (-*v).exp()));
+    }
+
+    /// x = tanh(x) for every element.
+    fn tanh_slice(x: &mut [Self]) {
+        x.iter_mut().for_each(|v| *v = v.tanh());
+    }
+
+    /// x += y element wise.
+    fn add_slice(x: &mut [Self], y: &[Self]) {
+        x.iter_mut().zip(y).for_each(|(a, &b)| *a += b);
+    }
+
+    /// x += s for every element.
+    fn add_scalar_slice(x: &mut [Self], s: Self) {
+        x.iter_mut().for_each(|v| *v += s);
+    }
+
+    /// x *= s for every element.
+    fn mul_scalar_slice(x: &mut [Self], s: Self) {
+        x.iter_mut().for_each(|v| *v *= s);
+    }
+}
+
+/// the methods that are the same for f32 and f64.
+macro_rules! float_impl {
+    () => {
+        const ZERO: Self = 0.0;
+        const ONE: Self = 1.0;
+        const EPSILON: Self = Self::EPSILON;
+        const NEG_INFINITY: Self = Self::NEG_INFINITY;
+
+        fn from_f64(v: f64) -> Self {
+            v as Self
+        }
+        fn to_f64(self) -> f64 {
+            self as f64
+        }
+        fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+            rng.gen()
+        }
+        fn sample_range<R: Rng + ?Sized>(rng: &mut R, range: Range<Self>) -> Self {
+            rng.gen_range(range)
+        }
+        fn sample_range_inclusive<R: Rng + ?Sized>(
+            rng: &mut R,
+            range: RangeInclusive<Self>,
+        ) -> Self {
+            rng.gen_range(range)
+        }
+        fn abs(self) -> Self {
+            self.abs()
+        }
+        fn signum(self) -> Self {
+            self.signum()
+        }
+        fn max(self, other: Self) -> Self {
+            self.max(other)
+        }
+        fn min(self, other: Self) -> Self {
+            self.min(other)
+        }
+        fn clamp(self, lo: Self, hi: Self) -> Self {
+            self.clamp(lo, hi)
+        }
+        fn sqrt(self) -> Self {
+            self.sqrt()
+        }
+        fn exp(self) -> Self {
+            self.exp()
+        }
+        fn ln(self) -> Self {
+            self.ln()
+        }
+        fn ln_1p(self) -> Self {
+            self.ln_1p()
+        }
+        fn tanh(self) -> Self {
+            self.tanh()
+        }
+        fn cos(self) -> Self {
+            self.cos()
+        }
+        fn powi(self, n: i32) -> Self {
+            self.powi(n)
+        }
+    };
+}
+
+impl Float for f32 {
+    float_impl!();
+
+    fn sigmoid_slice(x: &mut [f32]) {
+        simd::sigmoid(x);
+    }
+
+    fn tanh_slice(x: &mut [f32]) {
+        simd::tanh(x);
+    }
+
+    fn add_slice(x: &mut [f32], y: &[f32]) {
+        simd::add(x, y);
+    }
+
+    fn add_scalar_slice(x: &mut [f32], s: f32) {
+        simd::add_scalar(x, s);
+    }
+
+    fn mul_scalar_slice(x: &mut [f32], s: f32) {
+        simd::mul_scalar(x, s);
+    }
+}
+
+impl Float for f64 {
+    float_impl!();
+}
+
+/// the 16 bit types round every result of the f32 math back to their precision.
+macro_rules! half_impl {
+    ($t:ty) => {
+        impl Float for $t {
+            const ZERO: Self = <$t>::ZERO;
+            const ONE: Self = <$t>::ONE;
+            const EPSILON: Self = <$t>::EPSILON;
+            const NEG_INFINITY: Self = <$t>::NEG_INFINITY;
+
+            fn from_f64(v: f64) -> Self {
+                <$t>::from_f64(v)
+            }
+            fn to_f64(self) -> f64 {
+                <$t>::to_f64(self)
+            }
+            fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+                <$t>::from_f32(rng.gen())
+            }
+            fn sample_range<R: Rng + ?Sized>(rng: &mut R, range: Range<Self>) -> Self {
+                <$t>::from_f32(rng.gen_range(range.start.to_f32()..range.end.to_f32()))
+            }
+            fn sample_range_inclusive<R: Rng + ?Sized>(
+                rng: &mut R,
+                range: RangeInclusive<Self>,
+            ) -> Self {
+                <$t>::from_f32(rng.gen_range(range.start().to_f32()..=range.end().to_f32()))
+            }
+            fn abs(self) -> Self {
+                <$t>::from_f32(self.to_f32().abs())
+            }
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+            fn max(self, other: Self) -> Self {
+                <$t>::max(self, other)
+            }
+            fn min(self, other: Self) -> Self {
+                <$t>::min(self, other)
+            }
+            fn clamp(self, lo: Self, hi: Self) -> Self {
+                <$t>::clamp(self, lo, hi)
+            }
+            fn sqrt(self) -> Self {
+                <$t>::from_f32(self.to_f32().sqrt())
+            }
+            fn exp(self) -> Self {
+                <$t>::from_f32(self.to_f32().exp())
+            }
+            fn ln(self) -> Self {
+                <$t>::from_f32(self.to_f32().ln())
+            }
+            fn ln_1p(self) -> Self {
+                <$t>::from_f32(self.to_f32().ln_1p())
+            }
+            fn tanh(self) -> Self {
+                <$t>::from_f32(self.to_f32().tanh())
+            }
+            fn cos(self) -> Self {
+                <$t>::from_f32(self.to_f32().cos())
+            }
+            fn powi(self, n: i32) -> Self {
+                <$t>::from_f32(self.to_f32().powi(n))
+            }
+        }
+    };
+}
+
+half_impl!(f16);
+half_impl!(bf16);
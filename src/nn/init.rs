@@ -0,0 +1,128 @@
+use super::{Float, NNMatrix};
+use rand::Rng;
+
+/// strategy used to fill the weights or biases of a layer.
+/// fan_in is the number of inputs of the layer (rows of wl) and fan_out the number of
+/// outputs (cols of wl).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Initializer {
+    Zeros,
+    /// every value set to the constant, f64 so f64 models get it without rounding
+    Constant(f64),
+    /// uniform in -sqrt(6 / (fan_in + fan_out))..sqrt(6 / (fan_in + fan_out)), suits sigmoid and tanh
+    XavierUniform,
+    /// normal with std sqrt(2 / (fan_in + fan_out))
+    XavierNormal,
+    /// uniform in -sqrt(6 / fan_in)..sqrt(6 / fan_in), suits relu
+    HeUniform,
+    /// normal with std sqrt(2 / fan_in)
+    HeNormal,
+    /// uniform in -sqrt(3 / fan_in)..sqrt(3 / fan_in)
+    LecunUniform,
+    /// normal with std sqrt(1 / fan_in)
+    LecunNormal,
+    /// rows or columns, whichever are fewer, are orthonormal
+    Orthogonal,
+}
+
+impl Initializer {
+    /// fill m using thread_rng.
+    pub fn init<F: Float>(&self, m: &mut NNMatrix<F>, fan_in: usize, fan_out: usize) {
+        self.init_with(m, fan_in, fan_out, &mut rand::thread_rng());
+    }
+
+    /// fill m drawing from rng so the values can be reproduced.
+    pub fn init_with<F: Float, R: Rng + ?Sized>(
+        &self,
+        m: &mut NNMatrix<F>,
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut R,
+    ) {
+        let fan_in = F::from_f64(fan_in.max(1) as f64);
+        let fan_out = F::from_f64(fan_out.max(1) as f64);
+        let c = F::from_f64;
+        match *self {
+            Initializer::Zeros => m.fill(F::ZERO),
+            Initializer::Constant(value) => m.fill(F::from_f64(value)),
+            Initializer::XavierUniform => {
+                fill_uniform(m, (c(6.0) / (fan_in + fan_out)).sqrt(), rng)
+            }
+            Initializer::XavierNormal => fill_normal(m, (c(2.0) / (fan_in + fan_out)).sqrt(), rng),
+            Initializer::HeUniform => fill_uniform(m, (c(6.0) / fan_in).sqrt(), rng),
+            Initializer::HeNormal => fill_normal(m, (c(2.0) / fan_in).sqrt(), rng),
+            Initializer::LecunUniform => fill_uniform(m, (c(3.0) / fan_in).sqrt(), rng),
+            Initializer::LecunNormal => fill_normal(m, (c(1.0) / fan_in).sqrt(), rng),
+            Initializer::Orthogonal => fill_orthogonal(m, rng),
+        }
+    }
+}
+
+fn fill_uniform<F: Float, R: Rng + ?Sized>(m: &mut NNMatrix<F>, limit: F, rng: &mut R) {
+    for v in m.iter_mut() {
+        *v = F::sample_range_inclusive(rng, -limit..=limit);
+    }
+}
+
+/// standard normal sample using the box-muller transform.
+fn normal<F: Float, R: Rng + ?Sized>(rng: &mut R) -> F {
+    // 1 - gen() is in (0, 1] so ln never sees 0
+    let u1 = F::ONE - F::sample(rng);
+    let u2 = F::sample(rng);
+    let two = F::from_f64(2.0);
+    (-two * u1.ln()).sqrt() * (two * F::from_f64(std::f64::consts::PI) * u2).cos()
+}
+
+fn fill_normal<F: Float, R: Rng + ?Sized>(m: &mut NNMatrix<F>, std: F, rng: &mut R) {
+    for v in m.iter_mut() {
+        *v = std * normal(rng);
+    }
+}
+
+/// fill with a normal matrix and orthonormalize it with modified gram-schmidt.
+/// when rows >= cols the columns are orthonormal, otherwise the rows are.
+fn fill_orthogonal<F: Float, R: Rng + ?Sized>(m: &mut NNMatrix<F>, rng: &mut R) {
+    fill_normal(m, F::ONE, rng);
+    let by_cols = m.rows >= m.cols;
+    let (count, len) = if by_cols {
+        (m.cols, m.rows)
+    } else {
+        (m.rows, m.cols)
+    };
+    let at = |m: &NNMatrix<F>, v: usize, k: usize| {
+        if by_cols {
+            m.get_at(k, v)
+        } else {
+            m.get_at(v, k)
+        }
+    };
+    let set = |m: &mut NNMatrix<F>, v: usize, k: usize, value: F| {
+        if by_cols {
+            *m.get_mut_at(k, v) = value;
+        } else {
+            *m.get_mut_at(v, k) = value;
+        }
+    };
+
+    for v in 0..count {
+        for u in 0..v {
+            let mut dot = F::ZERO;
+            for k in 0..len {
+                dot += at(m, v, k) * at(m, u, k);
+            }
+            for k in 0..len {
+                let value = at(m, v, k) - dot * at(m, u, k);
+                set(m, v, k, value);
+            }
+        }
+        let mut norm = F::ZERO;
+        for k in 0..len {
+            norm += at(m, v, k) * at(m, v, k);
+        }
+        let norm = norm.sqrt().max(F::EPSILON);
+        for k in 0..len {
+            let value = at(m, v, k) / norm;
+            set(m, v, k, value);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::{check_row, Float, NNMatrix};
+
+impl<F: Float> NNMatrix<F> {
+    /// the cols values of row as a slice, without copying like get_row().
+    pub fn row(&self, row: usize) -> &[F] {
+        self.view().row(row)
+    }
+
+    /// the cols values of row as a mutable slice.
+    pub fn row_mut(&mut self, row: usize) -> &mut [F] {
+        check_row(row, (self.rows, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        let start = row * self.stride;
+        &mut self.data_frame[start..start + self.cols]
+    }
+
+    /// every row as a slice, top to bottom.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[F]> + DoubleEndedIterator {
+        self.view().rows()
+    }
+
+    /// every row as a mutable slice, top to bottom.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [F]> {
+        self.view_mut().into_rows_mut()
+    }
+
+    /// every element row by row, the values between cols and stride are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = &F> {
+        self.view().iter()
+    }
+
+    /// every element row by row, the values between cols and stride are skipped.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut F> {
+        self.rows_mut().flatten()
+    }
+
+    /// every element of column col, top to bottom. panics unless col < cols.
+    pub fn col_iter(&self, col: usize) -> impl ExactSizeIterator<Item = &F> {
+        self.view().col_iter(col)
+    }
+
+    /// iter() paired with the (row, col) of every element.
+    pub fn enumerate_indexed(&self) -> impl Iterator<Item = ((usize, usize), &F)> {
+        self.view().enumerate_indexed()
+    }
+}
+
+/// matrix with one row per item, every row needs the same length.
+impl<F: Float> FromIterator<Vec<F>> for NNMatrix<F> {
+    fn from_iter<I: IntoIterator<Item = Vec<F>>>(iter: I) -> Self {
+        let mut cols = None;
+        let mut rows = 0;
+        let mut data = Vec::new();
+        for row in iter {
+            let expected = *cols.get_or_insert(row.len());
+            assert!(
+                row.len() == expected,
+                "row {rows} has {} values, expected {expected}",
+                row.len()
+            );
+            data.extend(row);
+            rows += 1;
+        }
+        let cols = cols.unwrap_or(0);
+        NNMatrix::new(Some(&data), rows, cols, cols)
+    }
+}
+
+/// 1 x n matrix of the values.
+impl<F: Float> FromIterator<F> for NNMatrix<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        let data: Vec<F> = iter.into_iter().collect();
+        NNMatrix::new(Some(&data), 1, data.len(), data.len())
+    }
+}
+
+/// matrix with one row per inner vec, every row needs the same length.
+impl<F: Float> From<Vec<Vec<F>>> for NNMatrix<F> {
+    fn from(rows: Vec<Vec<F>>) -> Self {
+        rows.into_iter().collect()
+    }
+}
@@ -0,0 +1,168 @@
+use super::{Activation, Float, Loss, ModelFileError, NNArch, NNMatrix};
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const JSON_VERSION: u16 = 1;
+
+/// a matrix as its shape and nested rows, the stride is not kept.
+#[derive(Serialize, Deserialize)]
+struct MatrixJson<F> {
+    rows: usize,
+    cols: usize,
+    data: Vec<Vec<F>>,
+}
+
+impl<F: Float + Serialize> Serialize for NNMatrix<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let data = self.rows().map(|row| row.to_vec()).collect();
+        MatrixJson {
+            rows: self.rows,
+            cols: self.cols,
+            data,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, F: Float + Deserialize<'de>> Deserialize<'de> for NNMatrix<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = MatrixJson::<F>::deserialize(deserializer)?;
+        if json.data.len() != json.rows {
+            return Err(de::Error::custom(format!(
+                "expected {} rows found {}",
+                json.rows,
+                json.data.len()
+            )));
+        }
+        // every row is checked before anything is sized from rows and cols
+        for (i, row) in json.data.iter().enumerate() {
+            if row.len() != json.cols {
+                return Err(de::Error::custom(format!(
+                    "row {i}: expected {} cols found {}",
+                    json.cols,
+                    row.len()
+                )));
+            }
+        }
+        let data = json.data.concat();
+        Ok(NNMatrix::new(Some(&data), json.rows, json.cols, json.cols))
+    }
+}
+
+#[derive(Serialize)]
+struct ModelJsonRef<'a, F: Float> {
+    version: u16,
+    layers: Vec<usize>,
+    activations: &'a [Activation],
+    loss: Loss,
+    weights: &'a [NNMatrix<F>],
+    biases: &'a [NNMatrix<F>],
+}
+
+#[derive(Deserialize)]
+struct ModelJson<F: Float> {
+    version: u16,
+    layers: Vec<usize>,
+    activations: Vec<Activation>,
+    loss: Loss,
+    weights: Vec<NNMatrix<F>>,
+    biases: Vec<NNMatrix<F>>,
+}
+
+impl<F: Float> NNArch<F> {
+    /// the model as pretty printed json:
+    ///     { "version": 1, "layers": [2, 2, 1], "activations": ["Sigmoid", {"LeakyRelu": 0.1}],
+    ///       "loss": "Mse", "weights": [{"rows": 2, "cols": 2, "data": [[..], [..]]}, ..],
+    ///       "biases": [..] }
+    /// json has no nan or infinity, such values are written as null which from_json() rejects,
+    /// save_json() refuses to write them.
+    pub fn to_json(&self) -> String
+    where
+        F: Serialize,
+    {
+        let json = ModelJsonRef {
+            version: JSON_VERSION,
+            layers: self.arch(),
+            activations: &self.activations,
+            loss: self.loss,
+            weights: &self.wl,
+            biases: &self.bl,
+        };
+        serde_json::to_string_pretty(&json).expect("model is always serializable")
+    }
+
+    /// read a model from json written by to_json() or by hand, checking every matrix shape.
+    pub fn from_json(json: &str) -> Result<NNArch<F>, ModelFileError>
+    where
+        F: DeserializeOwned,
+    {
+        let json: ModelJson<F> = serde_json::from_str(json)?;
+        if json.version != JSON_VERSION {
+            return Err(ModelFileError::UnsupportedVersion(json.version));
+        }
+        if json.layers.len() < 2 {
+            return Err(ModelFileError::NoLayers);
+        }
+        let layer_count = json.layers.len() - 1;
+        for (name, len) in [
+            ("activations", json.activations.len()),
+            ("weights", json.weights.len()),
+            ("biases", json.biases.len()),
+        ] {
+            if len != layer_count {
+                return Err(ModelFileError::LayerCount {
+                    field: name,
+                    expected: layer_count,
+                    actual: len,
+                });
+            }
+        }
+
+        for (i, (w, b)) in json.weights.iter().zip(&json.biases).enumerate() {
+            for (m, expected) in [
+                (w, (json.layers[i], json.layers[i + 1])),
+                (b, (1, json.layers[i + 1])),
+            ] {
+                if (m.rows, m.cols) != expected {
+                    return Err(ModelFileError::ShapeMismatch {
+                        layer: i,
+                        expected,
+                        actual: (m.rows, m.cols),
+                    });
+                }
+            }
+        }
+        Ok(NNArch::from_layers(
+            json.weights,
+            json.biases,
+            &json.activations,
+            json.loss,
+        ))
+    }
+
+    /// write the model to path as json, see to_json().
+    /// fails with NonFinite instead of writing a file that can not be read back.
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), ModelFileError>
+    where
+        F: Serialize,
+    {
+        for (i, (w, b)) in self.wl.iter().zip(self.bl.iter()).enumerate() {
+            if w.iter().chain(b.iter()).any(|v| !v.to_f64().is_finite()) {
+                return Err(ModelFileError::NonFinite { layer: i });
+            }
+        }
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// read a model written by save_json().
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<NNArch<F>, ModelFileError>
+    where
+        F: DeserializeOwned,
+    {
+        NNArch::from_json(&fs::read_to_string(path)?)
+    }
+}
@@ -0,0 +1,468 @@
+//! building blocks of a model, a Sequential chains any number of boxed Layer values and a
+//! new kind of layer only has to implement the trait.
+//! NNArch runs on these too: forward, backprop, learn and Display move its weights and biases
+//! into a Dense and an ActivationLayer per layer with take_layers() and back with
+//! put_layers(). wl and bl stay the storage between calls, the model files, json,
+//! quantization and half storage read them.
+use super::{Activation, AsView, Float, Initializer, Loss, NNArch, NNMatrix, Optimizer, T};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+/// one step of a model. forward keeps whatever backward needs, so backward always refers to
+/// the last forward call.
+pub trait Layer<F: Float = T>: fmt::Debug + fmt::Display {
+    /// output for a batch of input rows.
+    fn forward(&mut self, input: &NNMatrix<F>) -> NNMatrix<F>;
+
+    /// gradient of the input from the gradient of the output, the gradients of the
+    /// parameters are added to grads().
+    fn backward(&mut self, grad: &NNMatrix<F>) -> NNMatrix<F>;
+
+    /// trainable matrices, empty for layers without parameters.
+    fn params(&self) -> Vec<&NNMatrix<F>> {
+        Vec::new()
+    }
+
+    /// gradient of every matrix of params(), in the same order.
+    fn grads(&self) -> Vec<&NNMatrix<F>> {
+        Vec::new()
+    }
+
+    /// every parameter next to its gradient, what an optimizer needs to update them.
+    fn params_and_grads(&mut self) -> Vec<(&mut NNMatrix<F>, &NNMatrix<F>)> {
+        Vec::new()
+    }
+
+    /// set every gradient to 0.
+    fn zero_grads(&mut self) {}
+
+    /// switch between training and inference, eg. dropout only drops while training.
+    fn set_training(&mut self, _training: bool) {}
+
+    /// the activation applied by the layer, lets Sequential fuse it with the loss.
+    fn activation(&self) -> Option<Activation> {
+        None
+    }
+}
+
+// ====================== dense start ==================================== //
+/// fully connected layer, output = input * w + b.
+#[derive(Debug, Clone)]
+pub struct Dense<F: Float = T> {
+    /// inputs x outputs
+    pub w: NNMatrix<F>,
+    /// 1 x outputs, added to every row
+    pub b: NNMatrix<F>,
+    pub dw: NNMatrix<F>,
+    pub db: NNMatrix<F>,
+    input: NNMatrix<F>,
+}
+
+impl<F: Float> Dense<F> {
+    /// dense layer with every weight and bias 0.
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Dense::from_params(
+            NNMatrix::empty(inputs, outputs),
+            NNMatrix::empty(1, outputs),
+        )
+    }
+
+    pub fn from_params(w: NNMatrix<F>, b: NNMatrix<F>) -> Self {
+        assert!(b.rows == 1 && b.cols == w.cols);
+        Dense {
+            dw: NNMatrix::empty(w.rows, w.cols),
+            db: NNMatrix::empty(1, b.cols),
+            input: NNMatrix::empty(0, w.rows),
+            w,
+            b,
+        }
+    }
+
+    pub fn initialize_with<R: Rng + ?Sized>(
+        &mut self,
+        weights: Initializer,
+        biases: Initializer,
+        rng: &mut R,
+    ) {
+        let (fan_in, fan_out) = (self.w.rows, self.w.cols);
+        weights.init_with(&mut self.w, fan_in, fan_out, rng);
+        biases.init_with(&mut self.b, fan_in, fan_out, rng);
+    }
+}
+
+impl<F: Float> Layer<F> for Dense<F> {
+    fn forward(&mut self, input: &NNMatrix<F>) -> NNMatrix<F> {
+        self.input.clone_from(input);
+        input * &self.w + &self.b
+    }
+
+    fn backward(&mut self, grad: &NNMatrix<F>) -> NNMatrix<F> {
+        self.dw += &self.input.t() * grad;
+        self.db += grad.sum_rows();
+        grad.matmul_transposed(&self.w)
+    }
+
+    fn params(&self) -> Vec<&NNMatrix<F>> {
+        vec![&self.w, &self.b]
+    }
+
+    fn grads(&self) -> Vec<&NNMatrix<F>> {
+        vec![&self.dw, &self.db]
+    }
+
+    fn params_and_grads(&mut self) -> Vec<(&mut NNMatrix<F>, &NNMatrix<F>)> {
+        vec![(&mut self.w, &self.dw), (&mut self.b, &self.db)]
+    }
+
+    fn zero_grads(&mut self) {
+        self.dw.fill(F::ZERO);
+        self.db.fill(F::ZERO);
+    }
+}
+
+impl<F: Float> fmt::Display for Dense<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dense {} -> {}", self.w.rows, self.w.cols)?;
+        write!(f, "w:{}", self.w)?;
+        write!(f, "b:{}", self.b)
+    }
+}
+// ====================== dense end ==================================== //
+
+// ====================== activation layer start ==================================== //
+/// applies an activation function, keeps the pre-activation and the output for backward.
+#[derive(Debug, Clone)]
+pub struct ActivationLayer<F: Float = T> {
+    pub function: Activation,
+    z: NNMatrix<F>,
+    a: NNMatrix<F>,
+}
+
+impl<F: Float> ActivationLayer<F> {
+    pub fn new(function: Activation) -> Self {
+        ActivationLayer {
+            function,
+            z: NNMatrix::empty(0, 0),
+            a: NNMatrix::empty(0, 0),
+        }
+    }
+}
+
+impl<F: Float> Layer<F> for ActivationLayer<F> {
+    fn forward(&mut self, input: &NNMatrix<F>) -> NNMatrix<F> {
+        self.z.clone_from(input);
+        self.a = NNMatrix::empty(input.rows, input.cols);
+        self.function.forward(&self.z, &mut self.a);
+        self.a.clone()
+    }
+
+    fn backward(&mut self, grad: &NNMatrix<F>) -> NNMatrix<F> {
+        let mut dz = NNMatrix::empty(grad.rows, grad.cols);
+        self.function.backward(&self.z, &self.a, grad, &mut dz);
+        dz
+    }
+
+    fn activation(&self) -> Option<Activation> {
+        Some(self.function)
+    }
+}
+
+impl<F: Float> fmt::Display for ActivationLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Activation {:?}", self.function)
+    }
+}
+// ====================== activation layer end ==================================== //
+
+// ====================== dropout start ==================================== //
+/// inverted dropout: while training every value is zeroed with probability rate and the rest
+/// are scaled by 1 / (1 - rate), so inference can skip the layer.
+#[derive(Debug, Clone)]
+pub struct Dropout<F: Float = T> {
+    pub rate: F,
+    pub training: bool,
+    mask: NNMatrix<F>,
+    rng: StdRng,
+}
+
+impl<F: Float> Dropout<F> {
+    /// the values to drop come from a generator seeded with seed, so every run drops the same
+    /// ones like a model made with NNArch::with_seed(). panics unless 0 <= rate < 1.
+    pub fn new(rate: F, seed: u64) -> Self {
+        assert!(
+            rate >= F::ZERO && rate < F::ONE,
+            "dropout rate {rate} not in 0..1"
+        );
+        Dropout {
+            rate,
+            training: true,
+            mask: NNMatrix::empty(0, 0),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<F: Float> Layer<F> for Dropout<F> {
+    fn forward(&mut self, input: &NNMatrix<F>) -> NNMatrix<F> {
+        if !self.training {
+            return input.clone();
+        }
+        let keep = F::ONE / (F::ONE - self.rate);
+        let rate = self.rate.to_f64();
+        self.mask = NNMatrix::empty(input.rows, input.cols);
+        for m in self.mask.iter_mut() {
+            *m = if self.rng.gen::<f64>() < rate {
+                F::ZERO
+            } else {
+                keep
+            };
+        }
+        input.hadamard(&self.mask)
+    }
+
+    fn backward(&mut self, grad: &NNMatrix<F>) -> NNMatrix<F> {
+        if !self.training {
+            return grad.clone();
+        }
+        grad.hadamard(&self.mask)
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+impl<F: Float> fmt::Display for Dropout<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dropout {}", self.rate)
+    }
+}
+// ====================== dropout end ==================================== //
+
+// ====================== sequential start ==================================== //
+/// layers applied one after the other, the output of each is the input of the next.
+#[derive(Debug)]
+pub struct Sequential<F: Float = T> {
+    pub layers: Vec<Box<dyn Layer<F>>>,
+    pub loss: Loss,
+}
+
+impl<F: Float> Default for Sequential<F> {
+    fn default() -> Self {
+        Sequential::new()
+    }
+}
+
+impl<F: Float> Sequential<F> {
+    pub fn new() -> Self {
+        Sequential {
+            layers: Vec::new(),
+            loss: Loss::Mse,
+        }
+    }
+
+    /// append layer after the current last layer.
+    pub fn with(mut self, layer: impl Layer<F> + 'static) -> Self {
+        self.push(layer);
+        self
+    }
+
+    pub fn with_loss(mut self, loss: Loss) -> Self {
+        self.loss = loss;
+        self
+    }
+
+    pub fn push(&mut self, layer: impl Layer<F> + 'static) {
+        self.layers.push(Box::new(layer));
+    }
+
+    /// switch every layer between training and inference.
+    pub fn set_training(&mut self, training: bool) {
+        for layer in self.layers.iter_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    /// every trainable matrix, layer by layer.
+    pub fn params(&self) -> Vec<&NNMatrix<F>> {
+        self.layers.iter().flat_map(|l| l.params()).collect()
+    }
+
+    /// gradient of every matrix of params() from the last backprop().
+    pub fn grads(&self) -> Vec<&NNMatrix<F>> {
+        self.layers.iter().flat_map(|l| l.grads()).collect()
+    }
+
+    /// feed all the rows of input through every layer.
+    pub fn forward(&mut self, input: &(impl AsView<Elem = F> + ?Sized)) -> NNMatrix<F> {
+        let mut a = input.as_view().to_matrix();
+        for layer in self.layers.iter_mut() {
+            a = layer.forward(&a);
+        }
+        a
+    }
+
+    /// forward() keeping the output of every layer, the last one is the output of the model.
+    pub fn forward_all(&mut self, input: &NNMatrix<F>) -> Vec<NNMatrix<F>> {
+        let mut outputs: Vec<NNMatrix<F>> = Vec::with_capacity(self.layers.len());
+        for layer in self.layers.iter_mut() {
+            let a = layer.forward(outputs.last().unwrap_or(input));
+            outputs.push(a);
+        }
+        outputs
+    }
+
+    /// zero the grads() and send grad, the gradient of the output of layer end - 1, back to
+    /// the first layer. returns the gradient of the input of every layer before end.
+    pub fn backward_from(&mut self, end: usize, mut grad: NNMatrix<F>) -> Vec<NNMatrix<F>> {
+        for layer in self.layers.iter_mut() {
+            layer.zero_grads();
+        }
+        let mut grads = Vec::with_capacity(end);
+        for layer in self.layers[..end].iter_mut().rev() {
+            grad = layer.backward(&grad);
+            grads.push(grad.clone());
+        }
+        grads.reverse();
+        grads
+    }
+
+    /// mean loss over the rows, like NNArch::cost the loss is computed from the
+    /// pre-activation when it fuses_with() the output activation.
+    pub fn cost(
+        &mut self,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+    ) -> F {
+        let df_output = df_output.as_view();
+        let (a, z) = self.forward_to_loss(df_input);
+        let mut cost = F::ZERO;
+        for (i, y) in df_output.rows().enumerate() {
+            cost += match &z {
+                Some(z) => self.loss.fused_value(z.row(i), y),
+                None => self.loss.value(a.row(i), y),
+            };
+        }
+        cost / F::from_usize(df_output.rows)
+    }
+
+    /// average the gradient of cost() over the rows into the grads() of every layer,
+    /// returns the cost.
+    pub fn backprop(
+        &mut self,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+    ) -> F {
+        let df_output = df_output.as_view();
+        let (a, z) = self.forward_to_loss(df_input);
+        assert!(a.rows == df_output.rows && a.cols == df_output.cols);
+        let n = F::from_usize(df_output.rows);
+        let mut cost = F::ZERO;
+        let mut grad = NNMatrix::empty(a.rows, a.cols);
+        for (i, y) in df_output.rows().enumerate() {
+            if let Some(z) = &z {
+                cost += self.loss.fused_value(z.row(i), y);
+                self.loss.fused_gradient(a.row(i), y, grad.row_mut(i));
+            } else {
+                cost += self.loss.value(a.row(i), y);
+                self.loss.gradient(a.row(i), y, grad.row_mut(i));
+            }
+        }
+        grad *= F::ONE / n;
+
+        // the fused gradient is already the gradient of the pre-activation
+        self.backward_from(self.layers.len() - usize::from(z.is_some()), grad);
+        cost / n
+    }
+
+    /// update every parameter from grads() with optimizer.
+    pub fn step(&mut self, optimizer: &mut dyn Optimizer<F>) {
+        let (mut params, grads): (Vec<&mut NNMatrix<F>>, Vec<&NNMatrix<F>>) = self
+            .layers
+            .iter_mut()
+            .flat_map(|l| l.params_and_grads())
+            .unzip();
+        optimizer.update(&mut params[..], &grads[..]);
+    }
+
+    /// run forward, returns the output and, when the loss fuses with the activation of the
+    /// last layer, the input of that layer.
+    fn forward_to_loss(
+        &mut self,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+    ) -> (NNMatrix<F>, Option<NNMatrix<F>>) {
+        let fused = self
+            .layers
+            .last()
+            .and_then(|l| l.activation())
+            .is_some_and(|a| self.loss.fuses_with(a));
+        let mut a = df_input.as_view().to_matrix();
+        let mut z = None;
+        let count = self.layers.len();
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            if fused && i + 1 == count {
+                z = Some(a.clone());
+            }
+            a = layer.forward(&a);
+        }
+        (a, z)
+    }
+}
+
+impl<F: Float> fmt::Display for Sequential<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Sequential {:?}", self.loss)?;
+        for (i, layer) in self.layers.iter().enumerate() {
+            write!(f, "{i}: {layer}")?;
+        }
+        Ok(())
+    }
+}
+
+/// a Dense layer followed by an ActivationLayer for every layer of the model.
+impl<F: Float> From<&NNArch<F>> for Sequential<F> {
+    fn from(model: &NNArch<F>) -> Self {
+        let mut seq = Sequential::new().with_loss(model.loss);
+        for i in 0..model.layer_count {
+            seq.push(Dense::from_params(model.wl[i].clone(), model.bl[i].clone()));
+            seq.push(ActivationLayer::new(model.activations[i]));
+        }
+        seq
+    }
+}
+
+impl<F: Float> NNArch<F> {
+    /// move the weights and biases into a Sequential of a Dense and an ActivationLayer per
+    /// layer, the Dense grads() start as the weights and biases of gradient when given.
+    /// put_layers() moves them back, until then wl and bl hold 0x0 matrices.
+    pub(super) fn take_layers(&mut self, gradient: Option<&NNArch<F>>) -> Sequential<F> {
+        let mut seq = Sequential::new().with_loss(self.loss);
+        for i in 0..self.layer_count {
+            let w = std::mem::replace(&mut self.wl[i], NNMatrix::empty(0, 0));
+            let b = std::mem::replace(&mut self.bl[i], NNMatrix::empty(0, 0));
+            let mut dense = Dense::from_params(w, b);
+            if let Some(gradient) = gradient {
+                dense.dw.clone_from(&gradient.wl[i]);
+                dense.db.clone_from(&gradient.bl[i]);
+            }
+            seq.push(dense);
+            seq.push(ActivationLayer::new(self.activations[i]));
+        }
+        seq
+    }
+
+    /// move the weights and biases of a Sequential made by take_layers() back into wl and bl.
+    pub(super) fn put_layers(&mut self, mut seq: Sequential<F>) {
+        let params = self
+            .wl
+            .iter_mut()
+            .zip(self.bl.iter_mut())
+            .flat_map(|(w, b)| [w, b]);
+        let taken = seq.layers.iter_mut().flat_map(|l| l.params_and_grads());
+        for (m, (p, _)) in params.zip(taken) {
+            std::mem::swap(m, p);
+        }
+    }
+}
+// ====================== sequential end ==================================== //
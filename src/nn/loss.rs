@@ -0,0 +1,130 @@
+use super::{Activation, Float};
+use serde::{Deserialize, Serialize};
+
+/// smallest probability used by the cross entropy losses so ln never sees 0.
+const PROB_EPS: f64 = 1e-7;
+
+/// loss function comparing the output of the model with the expected output of one sample.
+/// every loss is summed over the output columns, NNArch::cost averages it over the samples.
+/// the huber delta is f64 like the activation parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Loss {
+    /// squared error (y - a)^2
+    Mse,
+    /// absolute error |y - a|
+    Mae,
+    /// squared error below delta and absolute error above it.
+    Huber(f64),
+    /// -(y ln(a) + (1 - y) ln(1 - a)), expects outputs in 0..1
+    BinaryCrossEntropy,
+    /// -y ln(a), expects every output row to be a probability distribution
+    CategoricalCrossEntropy,
+}
+
+impl Loss {
+    /// loss of a single sample.
+    pub fn value<F: Float>(&self, predicted: &[F], expected: &[F]) -> F {
+        assert!(predicted.len() == expected.len());
+        let (one, half, eps) = (F::ONE, F::from_f64(0.5), F::from_f64(PROB_EPS));
+        let mut loss = F::ZERO;
+        for (&a, &y) in predicted.iter().zip(expected.iter()) {
+            loss += match *self {
+                Loss::Mse => (a - y) * (a - y),
+                Loss::Mae => (a - y).abs(),
+                Loss::Huber(delta) => {
+                    let delta: F = delta.cast();
+                    let diff = (a - y).abs();
+                    if diff <= delta {
+                        half * diff * diff
+                    } else {
+                        delta * (diff - half * delta)
+                    }
+                }
+                Loss::BinaryCrossEntropy => {
+                    let a = a.clamp(eps, one - eps);
+                    -(y * a.ln() + (one - y) * (one - a).ln())
+                }
+                Loss::CategoricalCrossEntropy => -y * a.max(eps).ln(),
+            };
+        }
+        loss
+    }
+
+    /// dloss/da of a single sample written into grad.
+    pub fn gradient<F: Float>(&self, predicted: &[F], expected: &[F], grad: &mut [F]) {
+        assert!(predicted.len() == expected.len() && grad.len() == predicted.len());
+        let (one, eps) = (F::ONE, F::from_f64(PROB_EPS));
+        for ((g, &a), &y) in grad.iter_mut().zip(predicted.iter()).zip(expected.iter()) {
+            *g = match *self {
+                Loss::Mse => F::from_f64(2.0) * (a - y),
+                Loss::Mae => (a - y).signum() * if a == y { F::ZERO } else { one },
+                Loss::Huber(delta) => {
+                    let delta: F = delta.cast();
+                    (a - y).clamp(-delta, delta)
+                }
+                Loss::BinaryCrossEntropy => {
+                    let a = a.clamp(eps, one - eps);
+                    (a - y) / (a * (one - a))
+                }
+                Loss::CategoricalCrossEntropy => -y / a.max(eps),
+            };
+        }
+    }
+
+    /// whether the loss has a combined form with the activation of the output layer.
+    /// softmax with categorical cross entropy and sigmoid with binary cross entropy both
+    /// simplify to a - y, which avoids dividing by probabilities close to 0.
+    pub fn fuses_with(&self, activation: Activation) -> bool {
+        matches!(
+            (*self, activation),
+            (Loss::CategoricalCrossEntropy, Activation::Softmax)
+                | (Loss::BinaryCrossEntropy, Activation::Sigmoid)
+        )
+    }
+
+    /// loss of a single sample computed from the pre-activation z of the output layer.
+    /// only valid when fuses_with() the output activation.
+    pub fn fused_value<F: Float>(&self, z: &[F], expected: &[F]) -> F {
+        assert!(z.len() == expected.len());
+        match *self {
+            Loss::CategoricalCrossEntropy => {
+                // -sum(y * log_softmax(z)) with log_softmax(z) = z - max - ln(sum(e^(z - max)))
+                let max = z.iter().fold(F::NEG_INFINITY, |acc, &v| acc.max(v));
+                let lse = max + z.iter().map(|&v| (v - max).exp()).sum::<F>().ln();
+                z.iter()
+                    .zip(expected.iter())
+                    .map(|(&v, &y)| -y * (v - lse))
+                    .sum()
+            }
+            Loss::BinaryCrossEntropy => {
+                // -(y ln(sigmoid(z)) + (1 - y) ln(1 - sigmoid(z))) = max(z, 0) - z y + ln(1 + e^-|z|)
+                z.iter()
+                    .zip(expected.iter())
+                    .map(|(&v, &y)| v.max(F::ZERO) - v * y + (-v.abs()).exp().ln_1p())
+                    .sum()
+            }
+            _ => panic!("{self:?} has no fused form"),
+        }
+    }
+
+    /// dloss/dz of a single sample for the output layer written into dz.
+    /// only valid when fuses_with() the output activation.
+    pub fn fused_gradient<F: Float>(&self, predicted: &[F], expected: &[F], dz: &mut [F]) {
+        assert!(predicted.len() == expected.len() && dz.len() == predicted.len());
+        match *self {
+            Loss::CategoricalCrossEntropy => {
+                // a * sum(y) - y, which is a - y when y is a distribution
+                let total: F = expected.iter().copied().sum();
+                for ((d, &a), &y) in dz.iter_mut().zip(predicted.iter()).zip(expected.iter()) {
+                    *d = a * total - y;
+                }
+            }
+            Loss::BinaryCrossEntropy => {
+                for ((d, &a), &y) in dz.iter_mut().zip(predicted.iter()).zip(expected.iter()) {
+                    *d = a - y;
+                }
+            }
+            _ => panic!("{self:?} has no fused form"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use super::{check_shape, AsView, Float, MatrixError, NNMatrixView, NNMatrixViewMut};
+use std::ops::Range;
+
+/// rows of a handled by one call of the microkernel.
+const MR: usize = 4;
+/// columns of b handled by one call of the microkernel, 8 f32 fill one avx register.
+const NR: usize = 8;
+/// depth of a block, KC rows of b stay in cache while every row of a walks over them.
+const KC: usize = 128;
+/// width of a block of columns of b.
+const NC: usize = 256;
+/// multiply adds below which a product is not split over threads.
+#[cfg(feature = "parallel")]
+const PAR_MIN_WORK: usize = 1 << 16;
+
+/// out = a * b without allocating, out needs a.rows x b.cols.
+/// panics when the shapes do not fit, see try_matmul_into().
+pub fn matmul_into<F: Float>(
+    a: &(impl AsView<Elem = F> + ?Sized),
+    b: &(impl AsView<Elem = F> + ?Sized),
+    out: &mut NNMatrixViewMut<F>,
+) {
+    try_matmul_into(a, b, out).unwrap_or_else(|e| panic!("{e}"));
+}
+
+/// matmul_into() that returns an error instead of panicking.
+/// the product is computed block by block: the columns of b are split in NC wide panels and
+/// the rows of b in KC deep slices, inside a block the microkernel keeps an MR x NR tile of
+/// out in registers while walking down k, the rows and columns left over at the edges use
+/// a plain i-k-j loop.
+pub fn try_matmul_into<F: Float>(
+    a: &(impl AsView<Elem = F> + ?Sized),
+    b: &(impl AsView<Elem = F> + ?Sized),
+    out: &mut NNMatrixViewMut<F>,
+) -> Result<(), MatrixError> {
+    matmul_views(&a.as_view(), &b.as_view(), out)
+}
+
+/// non generic body of try_matmul_into() so the kernel is compiled once.
+fn matmul_views<F: Float>(
+    a: &NNMatrixView<F>,
+    b: &NNMatrixView<F>,
+    out: &mut NNMatrixViewMut<F>,
+) -> Result<(), MatrixError> {
+    check_shape("matmul", (a.cols, b.cols), (b.rows, b.cols))?;
+    check_shape("matmul_into", (a.rows, b.cols), (out.rows, out.cols))?;
+    #[cfg(feature = "parallel")]
+    matmul_parallel(*a, b, out.submatrix_mut(0..out.rows, 0..out.cols));
+    #[cfg(not(feature = "parallel"))]
+    matmul_blocked(a, b, out);
+    Ok(())
+}
+
+/// split the rows of a and out in halves on threads until the blocks are small.
+/// the halves are cut at a multiple of MR so every row goes through the same kernel as
+/// in the serial product and the result is identical.
+#[cfg(feature = "parallel")]
+fn matmul_parallel<F: Float>(a: NNMatrixView<F>, b: &NNMatrixView<F>, mut out: NNMatrixViewMut<F>) {
+    let mid = a.rows / 2 / MR * MR;
+    if mid == 0 || a.rows * a.cols * b.cols < PAR_MIN_WORK {
+        matmul_blocked(&a, b, &mut out);
+        return;
+    }
+    let (a_top, a_bottom) = a.split_rows(mid);
+    let (out_top, out_bottom) = out.split_rows_mut(mid);
+    rayon::join(
+        || matmul_parallel(a_top, b, out_top),
+        || matmul_parallel(a_bottom, b, out_bottom),
+    );
+}
+
+/// out = a * b on the current thread.
+fn matmul_blocked<F: Float>(
+    a: &NNMatrixView<F>,
+    b: &NNMatrixView<F>,
+    out: &mut NNMatrixViewMut<F>,
+) {
+    out.fill(F::ZERO);
+
+    for jc in (0..b.cols).step_by(NC) {
+        let nc = NC.min(b.cols - jc);
+        for kc in (0..a.cols).step_by(KC) {
+            let ks = kc..kc + KC.min(a.cols - kc);
+            let tiled_rows = a.rows - a.rows % MR;
+            let tiled_cols = jc + nc - nc % NR;
+            for i in (0..tiled_rows).step_by(MR) {
+                for j in (jc..tiled_cols).step_by(NR) {
+                    kernel(a, b, i, j, ks.clone(), out);
+                }
+            }
+            edge(a, b, 0..tiled_rows, tiled_cols..jc + nc, ks.clone(), out);
+            edge(a, b, tiled_rows..a.rows, jc..jc + nc, ks, out);
+        }
+    }
+}
+
+/// out[i..i + MR][j..j + NR] += a[i..i + MR][ks] * b[ks][j..j + NR]
+#[inline(always)]
+fn kernel<F: Float>(
+    a: &NNMatrixView<F>,
+    b: &NNMatrixView<F>,
+    i: usize,
+    j: usize,
+    ks: Range<usize>,
+    out: &mut NNMatrixViewMut<F>,
+) {
+    let rows: [&[F]; MR] = std::array::from_fn(|r| &a.row(i + r)[ks.clone()]);
+    let mut acc = [[F::ZERO; NR]; MR];
+    for (k, kb) in ks.enumerate() {
+        let b_row: &[F; NR] = b.row(kb)[j..j + NR].try_into().unwrap();
+        for (a_row, acc_row) in rows.iter().zip(acc.iter_mut()) {
+            let a_val = a_row[k];
+            for c in 0..NR {
+                acc_row[c] += a_val * b_row[c];
+            }
+        }
+    }
+    for (r, acc_row) in acc.iter().enumerate() {
+        for (o, &v) in out.row_mut(i + r)[j..j + NR].iter_mut().zip(acc_row) {
+            *o += v;
+        }
+    }
+}
+
+/// out[rows][cols] += a[rows][ks] * b[ks][cols] with an i-k-j loop so b is read along rows.
+fn edge<F: Float>(
+    a: &NNMatrixView<F>,
+    b: &NNMatrixView<F>,
+    rows: Range<usize>,
+    cols: Range<usize>,
+    ks: Range<usize>,
+    out: &mut NNMatrixViewMut<F>,
+) {
+    if cols.is_empty() {
+        return;
+    }
+    for i in rows {
+        let a_row = a.row(i);
+        let out_row = &mut out.row_mut(i)[cols.clone()];
+        for k in ks.clone() {
+            let a_val = a_row[k];
+            for (o, &v) in out_row.iter_mut().zip(&b.row(k)[cols.clone()]) {
+                *o += a_val * v;
+            }
+        }
+    }
+}
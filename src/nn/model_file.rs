@@ -0,0 +1,527 @@
+use super::{Activation, Float, Half, HalfNN, Loss, NNArch, NNMatrix, Storage, T};
+use half::{bf16, f16};
+use std::any::TypeId;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MAGIC: [u8; 4] = *b"MMNN";
+const VERSION: u16 = 1;
+/// element type tag of f32 values.
+const ELEM_F32: u8 = 1;
+/// element type tag of f64 values.
+const ELEM_F64: u8 = 2;
+/// element type tag of f16 values.
+const ELEM_F16: u8 = 3;
+/// element type tag of bf16 values.
+const ELEM_BF16: u8 = 4;
+
+/// error returned when a model file can not be written or read back.
+#[derive(Debug)]
+pub enum ModelFileError {
+    Io(io::Error),
+    /// the file does not start with the MMNN magic bytes
+    BadMagic,
+    UnsupportedVersion(u16),
+    UnsupportedElementType(u8),
+    /// the file ended before all the values were read
+    Truncated,
+    /// bytes left over after the last layer
+    TrailingBytes(usize),
+    /// the architecture has no layers after the input
+    NoLayers,
+    /// a per layer list does not have one entry for every layer
+    LayerCount {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// the json text could not be parsed
+    Json(serde_json::Error),
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    InvalidActivation(u8),
+    InvalidLoss(u8),
+    /// a weight or bias matrix does not match the layer sizes
+    ShapeMismatch {
+        layer: usize,
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    /// a weight or bias of the layer is nan or infinite, which json can not hold
+    NonFinite {
+        layer: usize,
+    },
+}
+
+impl fmt::Display for ModelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelFileError::Io(e) => write!(f, "io error: {e}"),
+            ModelFileError::BadMagic => write!(f, "not a model file, bad magic bytes"),
+            ModelFileError::UnsupportedVersion(v) => write!(f, "unsupported version {v}"),
+            ModelFileError::UnsupportedElementType(t) => {
+                write!(f, "unsupported element type {t}")
+            }
+            ModelFileError::Truncated => write!(f, "model file is truncated"),
+            ModelFileError::TrailingBytes(n) => write!(f, "{n} bytes after the last layer"),
+            ModelFileError::NoLayers => write!(f, "model has no layers"),
+            ModelFileError::LayerCount {
+                field,
+                expected,
+                actual,
+            } => write!(f, "expected {expected} {field} found {actual}"),
+            ModelFileError::Json(e) => write!(f, "json error: {e}"),
+            ModelFileError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch, expected {expected:#010x} found {actual:#010x}"
+            ),
+            ModelFileError::InvalidActivation(t) => write!(f, "invalid activation tag {t}"),
+            ModelFileError::InvalidLoss(t) => write!(f, "invalid loss tag {t}"),
+            ModelFileError::ShapeMismatch {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "layer {layer}: expected {}x{} matrix found {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            ModelFileError::NonFinite { layer } => {
+                write!(f, "layer {layer}: nan or infinite value")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ModelFileError::Io(e) => Some(e),
+            ModelFileError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ModelFileError {
+    fn from(e: io::Error) -> Self {
+        ModelFileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ModelFileError {
+    fn from(e: serde_json::Error) -> Self {
+        ModelFileError::Json(e)
+    }
+}
+
+/// crc-32 (ieee) of bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn activation_tag(activation: Activation) -> (u8, f64) {
+    match activation {
+        Activation::Sigmoid => (0, 0.0),
+        Activation::Tanh => (1, 0.0),
+        Activation::Relu => (2, 0.0),
+        Activation::LeakyRelu(alpha) => (3, alpha),
+        Activation::Elu(alpha) => (4, alpha),
+        Activation::Gelu => (5, 0.0),
+        Activation::Softplus => (6, 0.0),
+        Activation::Identity => (7, 0.0),
+        Activation::Softmax => (8, 0.0),
+    }
+}
+
+fn activation_from_tag(tag: u8, param: f64) -> Result<Activation, ModelFileError> {
+    Ok(match tag {
+        0 => Activation::Sigmoid,
+        1 => Activation::Tanh,
+        2 => Activation::Relu,
+        3 => Activation::LeakyRelu(param),
+        4 => Activation::Elu(param),
+        5 => Activation::Gelu,
+        6 => Activation::Softplus,
+        7 => Activation::Identity,
+        8 => Activation::Softmax,
+        _ => return Err(ModelFileError::InvalidActivation(tag)),
+    })
+}
+
+fn loss_tag(loss: Loss) -> (u8, f64) {
+    match loss {
+        Loss::Mse => (0, 0.0),
+        Loss::Mae => (1, 0.0),
+        Loss::Huber(delta) => (2, delta),
+        Loss::BinaryCrossEntropy => (3, 0.0),
+        Loss::CategoricalCrossEntropy => (4, 0.0),
+    }
+}
+
+fn loss_from_tag(tag: u8, param: f64) -> Result<Loss, ModelFileError> {
+    Ok(match tag {
+        0 => Loss::Mse,
+        1 => Loss::Mae,
+        2 => Loss::Huber(param),
+        3 => Loss::BinaryCrossEntropy,
+        4 => Loss::CategoricalCrossEntropy,
+        _ => return Err(ModelFileError::InvalidLoss(tag)),
+    })
+}
+
+fn put_u32(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+/// element type tag of the values of F.
+fn elem_type<F: Float>() -> u8 {
+    let id = TypeId::of::<F>();
+    if id == TypeId::of::<f64>() {
+        ELEM_F64
+    } else if id == TypeId::of::<f16>() {
+        ELEM_F16
+    } else if id == TypeId::of::<bf16>() {
+        ELEM_BF16
+    } else {
+        ELEM_F32
+    }
+}
+
+/// storage of a half element type tag.
+fn storage_of(elem: u8) -> Option<Storage> {
+    match elem {
+        ELEM_F16 => Some(Storage::F16),
+        ELEM_BF16 => Some(Storage::Bf16),
+        _ => None,
+    }
+}
+
+/// everything before the layers, see NNArch::to_bytes().
+fn put_header(out: &mut Vec<u8>, elem: u8, arch: &[usize], activations: &[Activation], loss: Loss) {
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.push(elem);
+    put_u32(out, arch.len() - 1);
+    for &size in arch {
+        put_u32(out, size);
+    }
+    let tags = activations.iter().map(|&a| activation_tag(a));
+    for (tag, param) in tags.chain([loss_tag(loss)]) {
+        out.push(tag);
+        out.extend_from_slice(&param.to_le_bytes());
+    }
+}
+
+/// append the checksum of everything written so far.
+fn put_checksum(out: &mut Vec<u8>) {
+    let checksum = crc32(out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+}
+
+fn put_matrix<F: Float>(out: &mut Vec<u8>, m: &NNMatrix<F>) {
+    put_u32(out, m.rows);
+    put_u32(out, m.cols);
+    let elem = elem_type::<F>();
+    for value in m.iter() {
+        if elem == ELEM_F64 {
+            out.extend_from_slice(&value.to_f64().to_le_bytes());
+        } else if let Some(storage) = storage_of(elem) {
+            // a half value is exact in f32 so it encodes back to the same bits
+            out.extend_from_slice(&storage.encode(value.cast()).to_le_bytes());
+        } else {
+            out.extend_from_slice(&value.cast::<f32>().to_le_bytes());
+        }
+    }
+}
+
+/// reads little endian values from a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ModelFileError> {
+        if self.bytes.len() < n {
+            return Err(ModelFileError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ModelFileError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ModelFileError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<usize, ModelFileError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn f32(&mut self) -> Result<T, ModelFileError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, ModelFileError> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// a value stored with element type elem, converted to F.
+    fn value<F: Float>(&mut self, elem: u8) -> Result<F, ModelFileError> {
+        if elem == ELEM_F64 {
+            Ok(F::from_f64(self.f64()?))
+        } else if let Some(storage) = storage_of(elem) {
+            Ok(storage.decode(self.u16()?).cast())
+        } else {
+            Ok(self.f32()?.cast())
+        }
+    }
+
+    /// rows and cols of the next matrix, which have to be expected.
+    fn shape(&mut self, layer: usize, expected: (usize, usize)) -> Result<(), ModelFileError> {
+        let rows = self.u32()?;
+        let cols = self.u32()?;
+        if (rows, cols) != expected {
+            return Err(ModelFileError::ShapeMismatch {
+                layer,
+                expected,
+                actual: (rows, cols),
+            });
+        }
+        Ok(())
+    }
+
+    fn matrix<F: Float>(
+        &mut self,
+        elem: u8,
+        layer: usize,
+        expected: (usize, usize),
+    ) -> Result<NNMatrix<F>, ModelFileError> {
+        self.shape(layer, expected)?;
+        let (rows, cols) = expected;
+        let mut m = NNMatrix::empty(rows, cols);
+        for v in m.iter_mut() {
+            *v = self.value(elem)?;
+        }
+        Ok(m)
+    }
+}
+
+/// bytes taken by the layers of arch stored with element type elem, none on overflow.
+fn layer_bytes(elem: u8, arch: &[usize]) -> Option<usize> {
+    let size = match elem {
+        ELEM_F64 => 8,
+        ELEM_F32 => 4,
+        _ => 2,
+    };
+    arch.windows(2).try_fold(0usize, |total, pair| {
+        // u32 rows and cols then the values, for the weights and the bias
+        let weights = pair[0].checked_mul(pair[1])?.checked_add(pair[1])?;
+        total
+            .checked_add(16)?
+            .checked_add(weights.checked_mul(size)?)
+    })
+}
+
+/// the parts of a model file before the layers.
+struct Header<'a> {
+    reader: Reader<'a>,
+    elem: u8,
+    arch: Vec<usize>,
+    activations: Vec<Activation>,
+    loss: Loss,
+}
+
+/// check the magic, version and checksum of bytes and read everything before the layers.
+fn read_header(bytes: &[u8]) -> Result<Header<'_>, ModelFileError> {
+    if bytes.len() < MAGIC.len() + 4 {
+        return Err(ModelFileError::Truncated);
+    }
+    if bytes[..MAGIC.len()] != MAGIC {
+        return Err(ModelFileError::BadMagic);
+    }
+    let (body, tail) = bytes.split_at(bytes.len() - 4);
+    let mut reader = Reader {
+        bytes: &body[MAGIC.len()..],
+    };
+    let version = reader.u16()?;
+    if version != VERSION {
+        return Err(ModelFileError::UnsupportedVersion(version));
+    }
+    let expected = u32::from_le_bytes(tail.try_into().unwrap());
+    let actual = crc32(body);
+    if expected != actual {
+        return Err(ModelFileError::ChecksumMismatch { expected, actual });
+    }
+    let elem = reader.u8()?;
+    if !(ELEM_F32..=ELEM_BF16).contains(&elem) {
+        return Err(ModelFileError::UnsupportedElementType(elem));
+    }
+
+    // nothing is sized from the header until it is known to fit in the bytes
+    let layer_count = reader.u32()?;
+    let mut arch: Vec<usize> = Vec::new();
+    for _ in 0..=layer_count {
+        arch.push(reader.u32()?);
+    }
+    let mut activations: Vec<Activation> = Vec::with_capacity(layer_count);
+    for _ in 0..layer_count {
+        let tag = reader.u8()?;
+        activations.push(activation_from_tag(tag, reader.f64()?)?);
+    }
+    let tag = reader.u8()?;
+    let loss = loss_from_tag(tag, reader.f64()?)?;
+
+    if layer_count == 0 {
+        return Err(ModelFileError::NoLayers);
+    }
+    if layer_bytes(elem, &arch).is_none_or(|n| n > reader.bytes.len())
+        || arch.iter().any(|&size| size > reader.bytes.len())
+    {
+        return Err(ModelFileError::Truncated);
+    }
+    Ok(Header {
+        reader,
+        elem,
+        arch,
+        activations,
+        loss,
+    })
+}
+
+impl<F: Float> NNArch<F> {
+    /// write the model to path in the binary model format, see to_bytes().
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ModelFileError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// read a model written by save().
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<NNArch<F>, ModelFileError> {
+        NNArch::from_bytes(&fs::read(path)?)
+    }
+
+    /// encode the model, all values little endian:
+    ///     magic        4 bytes "MMNN"
+    ///     version      u16, currently 1
+    ///     element type u8, 1 = f32, 2 = f64, 3 = f16 or 4 = bf16 depending on F
+    ///     layer count  u32, number of layers excluding input
+    ///     layer sizes  u32 for every layer including input
+    ///     activations  u8 tag + f64 parameter for every layer
+    ///     loss         u8 tag + f64 parameter
+    ///     layers       for every layer wl then bl as u32 rows, u32 cols, rows * cols values
+    ///                  of the element type
+    ///     checksum     u32 crc-32 of every byte before it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::new();
+        put_header(
+            &mut out,
+            elem_type::<F>(),
+            &self.arch(),
+            &self.activations,
+            self.loss,
+        );
+        for i in 0..self.layer_count {
+            put_matrix(&mut out, &self.wl[i]);
+            put_matrix(&mut out, &self.bl[i]);
+        }
+        put_checksum(&mut out);
+        out
+    }
+
+    /// decode a model encoded by to_bytes(), checking the checksum and every matrix shape.
+    /// values of the other element types are converted to F.
+    pub fn from_bytes(bytes: &[u8]) -> Result<NNArch<F>, ModelFileError> {
+        let Header {
+            mut reader,
+            elem,
+            arch,
+            activations,
+            loss,
+        } = read_header(bytes)?;
+        let mut wl = Vec::with_capacity(arch.len() - 1);
+        let mut bl = Vec::with_capacity(arch.len() - 1);
+        for i in 0..arch.len() - 1 {
+            wl.push(reader.matrix(elem, i, (arch[i], arch[i + 1]))?);
+            bl.push(reader.matrix(elem, i, (1, arch[i + 1]))?);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(ModelFileError::TrailingBytes(reader.bytes.len()));
+        }
+        Ok(NNArch::from_layers(wl, bl, &activations, loss))
+    }
+}
+
+impl<H: Half> HalfNN<H> {
+    /// write the model to path in the binary model format with 16 bit values.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ModelFileError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// read a model written by HalfNN::save().
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HalfNN<H>, ModelFileError> {
+        HalfNN::from_bytes(&fs::read(path)?)
+    }
+
+    /// encode the model as NNArch::to_bytes() does with element type 3 = f16 or 4 = bf16,
+    /// every value takes 2 bytes. NNArch::from_bytes() reads these too.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::new();
+        put_header(
+            &mut out,
+            elem_type::<H>(),
+            &self.arch(),
+            &self.activations,
+            self.loss,
+        );
+        for i in 0..self.layer_count {
+            put_matrix(&mut out, &self.wl[i]);
+            put_matrix(&mut out, &self.bl()[i]);
+        }
+        put_checksum(&mut out);
+        out
+    }
+
+    /// decode a model encoded by HalfNN::to_bytes(), the values are kept in their storage.
+    /// files of the other element types are rejected, load those with NNArch and use
+    /// to_half().
+    pub fn from_bytes(bytes: &[u8]) -> Result<HalfNN<H>, ModelFileError> {
+        let Header {
+            mut reader,
+            elem,
+            arch,
+            activations,
+            loss,
+        } = read_header(bytes)?;
+        if elem != elem_type::<H>() {
+            return Err(ModelFileError::UnsupportedElementType(elem));
+        }
+        let mut wl = Vec::with_capacity(arch.len() - 1);
+        let mut bl = Vec::with_capacity(arch.len() - 1);
+        for i in 0..arch.len() - 1 {
+            wl.push(reader.matrix(elem, i, (arch[i], arch[i + 1]))?);
+            bl.push(reader.matrix(elem, i, (1, arch[i + 1]))?);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(ModelFileError::TrailingBytes(reader.bytes.len()));
+        }
+        Ok(HalfNN::from_layers(wl, bl, activations, loss))
+    }
+}
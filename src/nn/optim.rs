@@ -0,0 +1,296 @@
+use super::{Float, NNArch, NNMatrix, T};
+
+/// updates the weights and biases of a model from a gradient.
+/// optimizers that keep state hold one matrix per parameter shaped like wl/bl, created on
+/// the first step.
+pub trait Optimizer<F: Float = T> {
+    /// update every parameter with the gradient at the same position.
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]);
+
+    /// update the weights and biases of model with gradient.
+    fn step(&mut self, model: &mut NNArch<F>, gradient: &NNArch<F>) {
+        let grads = gradient.params();
+        let mut params = model.params_mut();
+        self.update(&mut params[..], &grads[..]);
+    }
+}
+
+/// panics unless there is one gradient shaped like every parameter.
+fn check_grads<F: Float>(params: &[&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+    assert!(
+        params.len() == grads.len(),
+        "{} parameters but {} gradients",
+        params.len(),
+        grads.len()
+    );
+    for (i, (p, g)) in params.iter().zip(grads.iter()).enumerate() {
+        assert!(
+            p.rows == g.rows && p.cols == g.cols,
+            "parameter {i} is {}x{} but its gradient is {}x{}",
+            p.rows,
+            p.cols,
+            g.rows,
+            g.cols
+        );
+    }
+}
+
+/// create one zeroed state matrix per parameter unless the state already matches them.
+fn init_state<F: Float>(state: &mut Vec<NNMatrix<F>>, params: &[&mut NNMatrix<F>]) {
+    let matches = state.len() == params.len()
+        && state
+            .iter()
+            .zip(params.iter())
+            .all(|(s, p)| s.rows == p.rows && s.cols == p.cols);
+    if !matches {
+        *state = params
+            .iter()
+            .map(|p| NNMatrix::empty(p.rows, p.cols))
+            .collect();
+    }
+}
+
+/// plain gradient descent, w -= rate * g. same as NNArch::learn.
+#[derive(Debug, Clone)]
+pub struct Sgd<F: Float = T> {
+    pub rate: F,
+}
+
+impl<F: Float> Sgd<F> {
+    pub fn new(rate: F) -> Self {
+        Sgd { rate }
+    }
+}
+
+impl<F: Float> Optimizer<F> for Sgd<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        check_grads(params, grads);
+        for (p, g) in params.iter_mut().zip(grads.iter()) {
+            for (p, &g) in p.iter_mut().zip(g.iter()) {
+                *p -= self.rate * g;
+            }
+        }
+    }
+}
+
+/// gradient descent with momentum
+///     v = momentum * v + g
+///     w -= rate * v                      or with nesterov
+///     w -= rate * (g + momentum * v)
+#[derive(Debug, Clone)]
+pub struct Momentum<F: Float = T> {
+    pub rate: F,
+    pub momentum: F,
+    pub nesterov: bool,
+    velocity: Vec<NNMatrix<F>>,
+}
+
+impl<F: Float> Momentum<F> {
+    pub fn new(rate: F, momentum: F) -> Self {
+        Momentum {
+            rate,
+            momentum,
+            nesterov: false,
+            velocity: Vec::new(),
+        }
+    }
+
+    /// momentum with the nesterov look ahead.
+    pub fn nesterov(rate: F, momentum: F) -> Self {
+        Momentum {
+            nesterov: true,
+            ..Momentum::new(rate, momentum)
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for Momentum<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        check_grads(params, grads);
+        init_state(&mut self.velocity, params);
+        for ((p, g), v) in params
+            .iter_mut()
+            .zip(grads.iter())
+            .zip(self.velocity.iter_mut())
+        {
+            for ((p, &grad), v) in p.iter_mut().zip(g.iter()).zip(v.iter_mut()) {
+                *v = self.momentum * *v + grad;
+                let delta = if self.nesterov {
+                    grad + self.momentum * *v
+                } else {
+                    *v
+                };
+                *p -= self.rate * delta;
+            }
+        }
+    }
+}
+
+/// gradient descent with a rate per parameter that shrinks with the sum of squared gradients
+///     s += g^2
+///     w -= rate * g / (sqrt(s) + eps)
+#[derive(Debug, Clone)]
+pub struct Adagrad<F: Float = T> {
+    pub rate: F,
+    pub eps: F,
+    sum_sq: Vec<NNMatrix<F>>,
+}
+
+impl<F: Float> Adagrad<F> {
+    pub fn new(rate: F) -> Self {
+        Adagrad {
+            rate,
+            eps: F::from_f64(1e-8),
+            sum_sq: Vec::new(),
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for Adagrad<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        check_grads(params, grads);
+        init_state(&mut self.sum_sq, params);
+        for ((p, g), s) in params
+            .iter_mut()
+            .zip(grads.iter())
+            .zip(self.sum_sq.iter_mut())
+        {
+            for ((p, &grad), s) in p.iter_mut().zip(g.iter()).zip(s.iter_mut()) {
+                *s += grad * grad;
+                *p -= self.rate * grad / (s.sqrt() + self.eps);
+            }
+        }
+    }
+}
+
+/// like adagrad but with a decaying average of the squared gradients
+///     s = decay * s + (1 - decay) * g^2
+///     w -= rate * g / (sqrt(s) + eps)
+#[derive(Debug, Clone)]
+pub struct RmsProp<F: Float = T> {
+    pub rate: F,
+    pub decay: F,
+    pub eps: F,
+    mean_sq: Vec<NNMatrix<F>>,
+}
+
+impl<F: Float> RmsProp<F> {
+    pub fn new(rate: F) -> Self {
+        RmsProp {
+            rate,
+            decay: F::from_f64(0.9),
+            eps: F::from_f64(1e-8),
+            mean_sq: Vec::new(),
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for RmsProp<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        check_grads(params, grads);
+        init_state(&mut self.mean_sq, params);
+        for ((p, g), s) in params
+            .iter_mut()
+            .zip(grads.iter())
+            .zip(self.mean_sq.iter_mut())
+        {
+            for ((p, &grad), s) in p.iter_mut().zip(g.iter()).zip(s.iter_mut()) {
+                *s = self.decay * *s + (F::ONE - self.decay) * grad * grad;
+                *p -= self.rate * grad / (s.sqrt() + self.eps);
+            }
+        }
+    }
+}
+
+/// adaptive moment estimation
+///     m = beta1 * m + (1 - beta1) * g
+///     v = beta2 * v + (1 - beta2) * g^2
+///     w -= rate * m' / (sqrt(v') + eps)
+/// where m' and v' are m and v corrected for starting at 0.
+#[derive(Debug, Clone)]
+pub struct Adam<F: Float = T> {
+    pub rate: F,
+    pub beta1: F,
+    pub beta2: F,
+    pub eps: F,
+    /// number of steps taken so far
+    pub t: i32,
+    m: Vec<NNMatrix<F>>,
+    v: Vec<NNMatrix<F>>,
+}
+
+impl<F: Float> Adam<F> {
+    pub fn new(rate: F) -> Self {
+        Adam {
+            rate,
+            beta1: F::from_f64(0.9),
+            beta2: F::from_f64(0.999),
+            eps: F::from_f64(1e-8),
+            t: 0,
+            m: Vec::new(),
+            v: Vec::new(),
+        }
+    }
+
+    /// adam step that also shrinks every parameter by rate * weight_decay * w.
+    fn update_decayed(
+        &mut self,
+        params: &mut [&mut NNMatrix<F>],
+        grads: &[&NNMatrix<F>],
+        weight_decay: F,
+    ) {
+        check_grads(params, grads);
+        init_state(&mut self.m, params);
+        init_state(&mut self.v, params);
+        self.t += 1;
+        let correct1 = F::ONE - self.beta1.powi(self.t);
+        let correct2 = F::ONE - self.beta2.powi(self.t);
+        for (((p, g), m), v) in params
+            .iter_mut()
+            .zip(grads.iter())
+            .zip(self.m.iter_mut())
+            .zip(self.v.iter_mut())
+        {
+            let values = p
+                .iter_mut()
+                .zip(g.iter())
+                .zip(m.iter_mut())
+                .zip(v.iter_mut());
+            for (((p, &grad), m), v) in values {
+                *m = self.beta1 * *m + (F::ONE - self.beta1) * grad;
+                *v = self.beta2 * *v + (F::ONE - self.beta2) * grad * grad;
+                let m_hat = *m / correct1;
+                let v_hat = *v / correct2;
+                *p -= self.rate * (m_hat / (v_hat.sqrt() + self.eps) + weight_decay * *p);
+            }
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for Adam<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        self.update_decayed(params, grads, F::ZERO);
+    }
+}
+
+/// adam with weight decay applied to the parameters directly instead of through the gradient.
+#[derive(Debug, Clone)]
+pub struct AdamW<F: Float = T> {
+    pub adam: Adam<F>,
+    pub weight_decay: F,
+}
+
+impl<F: Float> AdamW<F> {
+    pub fn new(rate: F, weight_decay: F) -> Self {
+        AdamW {
+            adam: Adam::new(rate),
+            weight_decay,
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for AdamW<F> {
+    fn update(&mut self, params: &mut [&mut NNMatrix<F>], grads: &[&NNMatrix<F>]) {
+        self.adam.update_decayed(params, grads, self.weight_decay);
+    }
+}
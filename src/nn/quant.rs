@@ -0,0 +1,351 @@
+use super::{Activation, AsView, Float, Loss, NNArch, NNMatrix, T};
+use std::fmt;
+
+/// affine mapping between f32 and int8, v = scale * (q - zero_point).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantParams {
+    pub scale: T,
+    pub zero_point: i32,
+}
+
+impl QuantParams {
+    /// params covering min..max, the range is widened to hold 0 so zero is exact.
+    pub fn from_range(min: T, max: T) -> Self {
+        let (min, max) = (min.min(0.0), max.max(0.0));
+        if max == min {
+            return QuantParams {
+                scale: 1.0,
+                zero_point: 0,
+            };
+        }
+        let scale = (max - min) / 255.0;
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i32;
+        QuantParams { scale, zero_point }
+    }
+
+    /// params covering -max_abs..max_abs with a zero point of 0.
+    pub fn symmetric(max_abs: T) -> Self {
+        QuantParams {
+            scale: if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 },
+            zero_point: 0,
+        }
+    }
+
+    /// nearest int8 of v, values outside the range are clamped.
+    pub fn quantize(&self, v: T) -> i8 {
+        ((v / self.scale).round() as i32 + self.zero_point).clamp(-128, 127) as i8
+    }
+
+    pub fn dequantize(&self, q: i8) -> T {
+        self.scale * (q as i32 - self.zero_point) as T
+    }
+}
+
+/// how many scales the weights of a layer share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// one scale for every weight of the layer
+    PerLayer,
+    /// one scale for every output column
+    PerChannel,
+}
+
+/// a layer with int8 weights, the bias is kept as i32 in the scale of the products.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantLayer {
+    pub rows: usize,
+    pub cols: usize,
+    /// rows * cols weights, rows are contiguous
+    pub weights: Box<[i8]>,
+    /// symmetric params of the weights, one per layer or one per column
+    pub weight_params: Box<[QuantParams]>,
+    /// bias of every column in units of input scale * weight scale
+    pub bias: Box<[i32]>,
+    /// params of the layer input, found by the calibration
+    pub input_params: QuantParams,
+    pub activation: Activation,
+}
+
+impl QuantLayer {
+    fn new<F: Float>(
+        w: &NNMatrix<F>,
+        b: &NNMatrix<F>,
+        input_params: QuantParams,
+        activation: Activation,
+        granularity: Granularity,
+    ) -> Self {
+        let weight_params: Box<[QuantParams]> = match granularity {
+            Granularity::PerLayer => {
+                let max_abs = w.iter().fold(0.0 as T, |m, v| m.max(v.abs().cast()));
+                Box::new([QuantParams::symmetric(max_abs)])
+            }
+            Granularity::PerChannel => (0..w.cols)
+                .map(|j| {
+                    let max_abs =
+                        (0..w.rows).fold(0.0 as T, |m, i| m.max(w.get_at(i, j).abs().cast()));
+                    QuantParams::symmetric(max_abs)
+                })
+                .collect(),
+        };
+        let mut layer = QuantLayer {
+            rows: w.rows,
+            cols: w.cols,
+            weights: vec![0; w.rows * w.cols].into_boxed_slice(),
+            weight_params,
+            bias: vec![0; w.cols].into_boxed_slice(),
+            input_params,
+            activation,
+        };
+        for j in 0..w.cols {
+            let params = layer.params(j);
+            for i in 0..w.rows {
+                layer.weights[i * w.cols + j] = params.quantize(w.get_at(i, j).cast());
+            }
+            let scale = input_params.scale * params.scale;
+            layer.bias[j] = (b.get_at(0, j).cast::<T>() / scale).round() as i32;
+        }
+        layer
+    }
+
+    /// weight params of column j.
+    pub fn params(&self, j: usize) -> QuantParams {
+        self.weight_params[j % self.weight_params.len()]
+    }
+
+    /// z = a * w + b with the products summed as i32, a holds rows quantized inputs.
+    fn matmul(&self, a: &[i8], rows: usize) -> NNMatrix {
+        let zero = self.input_params.zero_point;
+        let mut acc = vec![0i32; self.cols];
+        let mut z = NNMatrix::empty(rows, self.cols);
+        for i in 0..rows {
+            acc.copy_from_slice(&self.bias);
+            for (k, &q) in a[i * self.rows..(i + 1) * self.rows].iter().enumerate() {
+                let q = q as i32 - zero;
+                if q == 0 {
+                    continue;
+                }
+                let w = &self.weights[k * self.cols..(k + 1) * self.cols];
+                acc.iter_mut().zip(w).for_each(|(s, &w)| *s += q * w as i32);
+            }
+            for (j, &s) in acc.iter().enumerate() {
+                z[(i, j)] = s as T * self.input_params.scale * self.params(j).scale;
+            }
+        }
+        z
+    }
+}
+
+/// int8 copy of a model made by NNArch::quantize(), only the activation functions run in f32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedNN {
+    pub layers: Box<[QuantLayer]>,
+    pub loss: Loss,
+    pub granularity: Granularity,
+}
+
+impl<F: Float> NNArch<F> {
+    /// quantize() with one weight scale per output column.
+    pub fn quantize(&mut self, calibration: &(impl AsView<Elem = F> + ?Sized)) -> QuantizedNN {
+        self.quantize_with(calibration, Granularity::PerChannel)
+    }
+
+    /// int8 copy of the model. calibration is fed through the model to find the range of
+    /// every layer input, so it should look like the data the model will see.
+    pub fn quantize_with(
+        &mut self,
+        calibration: &(impl AsView<Elem = F> + ?Sized),
+        granularity: Granularity,
+    ) -> QuantizedNN {
+        self.forward_batch(calibration);
+        let layers = (0..self.layer_count)
+            .map(|i| {
+                let a = &self.al[i];
+                let (min, max) = a.iter().fold((0.0 as T, 0.0 as T), |(lo, hi), v| {
+                    let v: T = v.cast();
+                    (lo.min(v), hi.max(v))
+                });
+                let input_params = QuantParams::from_range(min, max);
+                QuantLayer::new(
+                    &self.wl[i],
+                    &self.bl[i],
+                    input_params,
+                    self.activations[i],
+                    granularity,
+                )
+            })
+            .collect();
+        QuantizedNN {
+            layers,
+            loss: self.loss,
+            granularity,
+        }
+    }
+}
+
+impl QuantizedNN {
+    /// bytes taken by the weights, biases and params.
+    pub fn param_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|l| {
+                l.weights.len()
+                    + l.bias.len() * std::mem::size_of::<i32>()
+                    + (l.weight_params.len() + 1) * std::mem::size_of::<QuantParams>()
+            })
+            .sum()
+    }
+
+    /// output of every row of input, the layer inputs are quantized with the calibrated params
+    /// and multiplied with the int8 weights.
+    pub fn forward_batch(&self, input: &(impl AsView<Elem = T> + ?Sized)) -> NNMatrix {
+        self.forward_layers(input).1
+    }
+
+    /// forward_batch() that also returns the pre-activation of the output layer.
+    fn forward_layers(&self, input: &(impl AsView<Elem = T> + ?Sized)) -> (NNMatrix, NNMatrix) {
+        let input = input.as_view();
+        assert!(input.cols == self.layers[0].rows);
+        let mut a: NNMatrix = NNMatrix::empty(input.rows, input.cols);
+        for i in 0..input.rows {
+            a.copy_row(i, &input, i);
+        }
+        let mut z = NNMatrix::empty(input.rows, 0);
+        let mut q: Vec<i8> = Vec::new();
+        for layer in self.layers.iter() {
+            q.clear();
+            q.extend(a.iter().map(|&v| layer.input_params.quantize(v)));
+            z = layer.matmul(&q, a.rows);
+            a = NNMatrix::empty(z.rows, z.cols);
+            layer.activation.forward(&z, &mut a);
+        }
+        (z, a)
+    }
+
+    /// cost of the model with its loss, like NNArch::cost().
+    /// when the loss fuses_with() the output activation the combined form is used on the
+    /// pre-activation, like HalfNN::cost().
+    pub fn cost(
+        &self,
+        df_input: &(impl AsView<Elem = T> + ?Sized),
+        df_output: &(impl AsView<Elem = T> + ?Sized),
+    ) -> T {
+        let df_output = df_output.as_view();
+        let (z, output) = self.forward_layers(df_input);
+        assert!(output.rows == df_output.rows);
+        let last = self.layers[self.layers.len() - 1].activation;
+        let fused = self.loss.fuses_with(last);
+        let cost: T = (0..output.rows)
+            .map(|i| {
+                if fused {
+                    self.loss.fused_value(z.row(i), df_output.row(i))
+                } else {
+                    self.loss.value(output.row(i), df_output.row(i))
+                }
+            })
+            .sum();
+        cost / output.rows as T
+    }
+
+    /// compare the outputs, cost and accuracy with the float model this was made from.
+    pub fn evaluate(
+        &self,
+        model: &mut NNArch,
+        df_input: &(impl AsView<Elem = T> + ?Sized),
+        df_output: &(impl AsView<Elem = T> + ?Sized),
+    ) -> QuantReport {
+        let labels = df_output.as_view();
+        let float = model.forward_batch(df_input).clone();
+        let quant = self.forward_batch(df_input);
+        let mut report = QuantReport {
+            max_abs: 0.0,
+            mean_abs: 0.0,
+            cost: model.cost(df_input, df_output),
+            quant_cost: self.cost(df_input, df_output),
+            accuracy: 0.0,
+            quant_accuracy: 0.0,
+            bytes: model
+                .params()
+                .iter()
+                .map(|m| m.rows * m.cols)
+                .sum::<usize>()
+                * std::mem::size_of::<T>(),
+            quant_bytes: self.param_bytes(),
+        };
+        for i in 0..float.rows {
+            let (f, q) = (float.get_row(i), quant.get_row(i));
+            for (a, b) in f.iter().zip(q.iter()) {
+                report.max_abs = report.max_abs.max((a - b).abs());
+                report.mean_abs += (a - b).abs();
+            }
+            let expected = class(labels.row(i));
+            report.accuracy += (class(&f) == expected) as u8 as T;
+            report.quant_accuracy += (class(&q) == expected) as u8 as T;
+        }
+        let rows = float.rows.max(1) as T;
+        report.mean_abs /= rows * float.cols as T;
+        report.accuracy /= rows;
+        report.quant_accuracy /= rows;
+        report
+    }
+}
+
+/// class of an output row, the largest column or value > 0.5 for a single column.
+fn class(row: &[T]) -> usize {
+    if row.len() == 1 {
+        return (row[0] > 0.5) as usize;
+    }
+    (0..row.len()).fold(0, |best, j| if row[j] > row[best] { j } else { best })
+}
+
+/// difference between a QuantizedNN and the float model it was made from on the same data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantReport {
+    /// largest absolute difference of an output value
+    pub max_abs: T,
+    /// mean absolute difference of the output values
+    pub mean_abs: T,
+    /// cost of the float model
+    pub cost: T,
+    /// cost of the quantized model
+    pub quant_cost: T,
+    /// fraction of rows the float model predicts the expected class for
+    pub accuracy: T,
+    /// fraction of rows the quantized model predicts the expected class for
+    pub quant_accuracy: T,
+    /// size of the f32 weights and biases
+    pub bytes: usize,
+    /// size of the int8 weights, i32 biases and params
+    pub quant_bytes: usize,
+}
+
+impl QuantReport {
+    /// accuracy lost by quantizing, negative when the quantized model does better.
+    pub fn accuracy_drop(&self) -> T {
+        self.accuracy - self.quant_accuracy
+    }
+}
+
+impl fmt::Display for QuantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "int8: output max abs {:.3e} mean abs {:.3e}",
+            self.max_abs, self.mean_abs
+        )?;
+        writeln!(
+            f,
+            "cost f32 {:.6} int8 {:.6} ({:+.3e})",
+            self.cost,
+            self.quant_cost,
+            self.quant_cost - self.cost
+        )?;
+        writeln!(
+            f,
+            "accuracy f32 {:.2}% int8 {:.2}% (drop {:.2}%)",
+            self.accuracy * 100.0,
+            self.quant_accuracy * 100.0,
+            self.accuracy_drop() * 100.0
+        )?;
+        write!(f, "size {} -> {} bytes", self.bytes, self.quant_bytes)
+    }
+}
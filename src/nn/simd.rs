@@ -0,0 +1,452 @@
+use super::T;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+// cephes style exp: e^x = 2^n * e^r with n = round(x / ln 2) and |r| <= ln 2 / 2,
+// e^r comes from a degree 7 polynomial, 2^n is built directly in the exponent bits.
+const EXP_HI: T = 88.0;
+/// ln of the smallest normal f32, below it 2^n would need a denormal exponent.
+const EXP_LO: T = -87.336_55;
+const LOG2E: T = std::f32::consts::LOG2_E;
+/// ln 2 split in an exact high part and the rest so n * ln 2 keeps its precision.
+const LN2_HI: T = 0.693_359_4;
+const LN2_LO: T = -2.121_944_4e-4;
+const P0: T = 0.5;
+const P1: T = 1.666_666_5e-1;
+const P2: T = 4.166_579_6e-2;
+const P3: T = 8.333_452e-3;
+const P4: T = 1.398_2e-3;
+const P5: T = 1.987_569_1e-4;
+/// below this tanh uses its taylor series, (1 - e) / (1 + e) loses too many bits to cancel.
+const TANH_SMALL: T = 0.0625;
+
+/// instruction set used by the kernels, detected at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// 8 lanes with fused multiply add
+    Avx2,
+    /// 4 lanes
+    Sse41,
+    Scalar,
+}
+
+/// the best instruction set this cpu supports, the kernels below use it.
+pub fn level() -> Level {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return Level::Avx2;
+        }
+        if is_x86_feature_detected!("sse4.1") {
+            return Level::Sse41;
+        }
+    }
+    Level::Scalar
+}
+
+/// whether this cpu can run the kernels of level.
+pub fn supported(level: Level) -> bool {
+    match (level, self::level()) {
+        (Level::Scalar, _) | (_, Level::Avx2) => true,
+        (Level::Sse41, best) => best == Level::Sse41,
+        (Level::Avx2, _) => false,
+    }
+}
+
+/// the *_at kernels of an unsupported level would be undefined behaviour.
+fn check_level(level: Level) {
+    assert!(
+        supported(level),
+        "simd level {level:?} is not supported by this cpu"
+    );
+}
+
+/// e^x with a relative error below 1e-6 for x in -87..88, saturating outside of it.
+pub fn exp_approx(x: T) -> T {
+    let x = x.clamp(EXP_LO, EXP_HI);
+    let n = (x * LOG2E).round();
+    let r = x - n * LN2_HI - n * LN2_LO;
+    let p = ((((P5 * r + P4) * r + P3) * r + P2) * r + P1) * r + P0;
+    let e = p * r * r + r + 1.0;
+    e * T::from_bits(((n as i32 + 127) as u32) << 23)
+}
+
+/// 1 / (1 + e^-x) using exp_approx().
+pub fn sigmoid_approx(x: T) -> T {
+    1.0 / (1.0 + exp_approx(-x))
+}
+
+/// tanh using exp_approx().
+pub fn tanh_approx(x: T) -> T {
+    let a = x.abs();
+    if a < TANH_SMALL {
+        let x2 = x * x;
+        return x + x * x2 * (-1.0 / 3.0 + x2 * (2.0 / 15.0));
+    }
+    let e = exp_approx(-2.0 * a);
+    ((1.0 - e) / (1.0 + e)).copysign(x)
+}
+
+/// x = e^x for every element.
+pub fn exp(x: &mut [T]) {
+    exp_at(level(), x);
+}
+
+/// exp() with the kernels of level.
+pub fn exp_at(level: Level, x: &mut [T]) {
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::exp(x) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::exp(x) },
+        _ => x.iter_mut().for_each(|v| *v = exp_approx(*v)),
+    }
+}
+
+/// x = 1 / (1 + e^-x) for every element.
+pub fn sigmoid(x: &mut [T]) {
+    sigmoid_at(level(), x);
+}
+
+/// sigmoid() with the kernels of level.
+pub fn sigmoid_at(level: Level, x: &mut [T]) {
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::sigmoid(x) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::sigmoid(x) },
+        _ => x.iter_mut().for_each(|v| *v = sigmoid_approx(*v)),
+    }
+}
+
+/// x = tanh(x) for every element.
+pub fn tanh(x: &mut [T]) {
+    tanh_at(level(), x);
+}
+
+/// tanh() with the kernels of level.
+pub fn tanh_at(level: Level, x: &mut [T]) {
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::tanh(x) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::tanh(x) },
+        _ => x.iter_mut().for_each(|v| *v = tanh_approx(*v)),
+    }
+}
+
+/// x += y element by element, both need the same length.
+pub fn add(x: &mut [T], y: &[T]) {
+    add_at(level(), x, y);
+}
+
+/// add() with the kernels of level.
+pub fn add_at(level: Level, x: &mut [T], y: &[T]) {
+    assert!(x.len() == y.len());
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::add(x, y) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::add(x, y) },
+        _ => x.iter_mut().zip(y).for_each(|(a, b)| *a += b),
+    }
+}
+
+/// x += s for every element.
+pub fn add_scalar(x: &mut [T], s: T) {
+    add_scalar_at(level(), x, s);
+}
+
+/// add_scalar() with the kernels of level.
+pub fn add_scalar_at(level: Level, x: &mut [T], s: T) {
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::add_scalar(x, s) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::add_scalar(x, s) },
+        _ => x.iter_mut().for_each(|v| *v += s),
+    }
+}
+
+/// x *= s for every element.
+pub fn mul_scalar(x: &mut [T], s: T) {
+    mul_scalar_at(level(), x, s);
+}
+
+/// mul_scalar() with the kernels of level.
+pub fn mul_scalar_at(level: Level, x: &mut [T], s: T) {
+    check_level(level);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        Level::Avx2 => unsafe { avx2::mul_scalar(x, s) },
+        #[cfg(target_arch = "x86_64")]
+        Level::Sse41 => unsafe { sse41::mul_scalar(x, s) },
+        _ => x.iter_mut().for_each(|v| *v *= s),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::*;
+
+    const LANES: usize = 8;
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn exp8(x: __m256) -> __m256 {
+        // min and max return their second operand when either is nan, so nan passes through
+        let x = _mm256_min_ps(
+            _mm256_set1_ps(EXP_HI),
+            _mm256_max_ps(_mm256_set1_ps(EXP_LO), x),
+        );
+        let n = _mm256_round_ps::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(
+            _mm256_mul_ps(x, _mm256_set1_ps(LOG2E)),
+        );
+        let r = _mm256_fnmadd_ps(n, _mm256_set1_ps(LN2_HI), x);
+        let r = _mm256_fnmadd_ps(n, _mm256_set1_ps(LN2_LO), r);
+        let mut p = _mm256_set1_ps(P5);
+        for c in [P4, P3, P2, P1, P0] {
+            p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(c));
+        }
+        let e = _mm256_fmadd_ps(
+            _mm256_mul_ps(p, r),
+            r,
+            _mm256_add_ps(r, _mm256_set1_ps(1.0)),
+        );
+        let pow2n = _mm256_slli_epi32::<23>(_mm256_add_epi32(
+            _mm256_cvtps_epi32(n),
+            _mm256_set1_epi32(127),
+        ));
+        _mm256_mul_ps(e, _mm256_castsi256_ps(pow2n))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn sigmoid8(x: __m256) -> __m256 {
+        let one = _mm256_set1_ps(1.0);
+        let e = exp8(_mm256_sub_ps(_mm256_setzero_ps(), x));
+        _mm256_div_ps(one, _mm256_add_ps(one, e))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn tanh8(x: __m256) -> __m256 {
+        let one = _mm256_set1_ps(1.0);
+        let sign = _mm256_set1_ps(-0.0);
+        let a = _mm256_andnot_ps(sign, x);
+        let e = exp8(_mm256_mul_ps(a, _mm256_set1_ps(-2.0)));
+        let big = _mm256_div_ps(_mm256_sub_ps(one, e), _mm256_add_ps(one, e));
+        let big = _mm256_or_ps(big, _mm256_and_ps(sign, x));
+        let x2 = _mm256_mul_ps(x, x);
+        let series = _mm256_fmadd_ps(x2, _mm256_set1_ps(2.0 / 15.0), _mm256_set1_ps(-1.0 / 3.0));
+        let small = _mm256_fmadd_ps(_mm256_mul_ps(x, x2), series, x);
+        let is_small = _mm256_cmp_ps::<_CMP_LT_OQ>(a, _mm256_set1_ps(TANH_SMALL));
+        _mm256_blendv_ps(big, small, is_small)
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn exp(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm256_storeu_ps(c.as_mut_ptr(), exp8(_mm256_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = exp_approx(*v));
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn sigmoid(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm256_storeu_ps(c.as_mut_ptr(), sigmoid8(_mm256_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = sigmoid_approx(*v));
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn tanh(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm256_storeu_ps(c.as_mut_ptr(), tanh8(_mm256_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = tanh_approx(*v));
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn add(x: &mut [T], y: &[T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        let mut y_chunks = y.chunks_exact(LANES);
+        for (c, d) in (&mut chunks).zip(&mut y_chunks) {
+            let sum = _mm256_add_ps(_mm256_loadu_ps(c.as_ptr()), _mm256_loadu_ps(d.as_ptr()));
+            _mm256_storeu_ps(c.as_mut_ptr(), sum);
+        }
+        for (a, b) in chunks.into_remainder().iter_mut().zip(y_chunks.remainder()) {
+            *a += b;
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn add_scalar(x: &mut [T], s: T) {
+        let sv = _mm256_set1_ps(s);
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm256_storeu_ps(
+                c.as_mut_ptr(),
+                _mm256_add_ps(_mm256_loadu_ps(c.as_ptr()), sv),
+            );
+        }
+        chunks.into_remainder().iter_mut().for_each(|v| *v += s);
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn mul_scalar(x: &mut [T], s: T) {
+        let sv = _mm256_set1_ps(s);
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm256_storeu_ps(
+                c.as_mut_ptr(),
+                _mm256_mul_ps(_mm256_loadu_ps(c.as_ptr()), sv),
+            );
+        }
+        chunks.into_remainder().iter_mut().for_each(|v| *v *= s);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse41 {
+    use super::*;
+
+    const LANES: usize = 4;
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn exp4(x: __m128) -> __m128 {
+        // x last so nan passes through like in the scalar clamp
+        let x = _mm_min_ps(_mm_set1_ps(EXP_HI), _mm_max_ps(_mm_set1_ps(EXP_LO), x));
+        let n = _mm_round_ps::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(_mm_mul_ps(
+            x,
+            _mm_set1_ps(LOG2E),
+        ));
+        let r = _mm_sub_ps(x, _mm_mul_ps(n, _mm_set1_ps(LN2_HI)));
+        let r = _mm_sub_ps(r, _mm_mul_ps(n, _mm_set1_ps(LN2_LO)));
+        let mut p = _mm_set1_ps(P5);
+        for c in [P4, P3, P2, P1, P0] {
+            p = _mm_add_ps(_mm_mul_ps(p, r), _mm_set1_ps(c));
+        }
+        let e = _mm_add_ps(
+            _mm_mul_ps(_mm_mul_ps(p, r), r),
+            _mm_add_ps(r, _mm_set1_ps(1.0)),
+        );
+        let pow2n = _mm_slli_epi32::<23>(_mm_add_epi32(_mm_cvtps_epi32(n), _mm_set1_epi32(127)));
+        _mm_mul_ps(e, _mm_castsi128_ps(pow2n))
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn sigmoid4(x: __m128) -> __m128 {
+        let one = _mm_set1_ps(1.0);
+        let e = exp4(_mm_sub_ps(_mm_setzero_ps(), x));
+        _mm_div_ps(one, _mm_add_ps(one, e))
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn tanh4(x: __m128) -> __m128 {
+        let one = _mm_set1_ps(1.0);
+        let sign = _mm_set1_ps(-0.0);
+        let a = _mm_andnot_ps(sign, x);
+        let e = exp4(_mm_mul_ps(a, _mm_set1_ps(-2.0)));
+        let big = _mm_div_ps(_mm_sub_ps(one, e), _mm_add_ps(one, e));
+        let big = _mm_or_ps(big, _mm_and_ps(sign, x));
+        let x2 = _mm_mul_ps(x, x);
+        let series = _mm_add_ps(
+            _mm_mul_ps(x2, _mm_set1_ps(2.0 / 15.0)),
+            _mm_set1_ps(-1.0 / 3.0),
+        );
+        let small = _mm_add_ps(_mm_mul_ps(_mm_mul_ps(x, x2), series), x);
+        let is_small = _mm_cmplt_ps(a, _mm_set1_ps(TANH_SMALL));
+        _mm_blendv_ps(big, small, is_small)
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn exp(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm_storeu_ps(c.as_mut_ptr(), exp4(_mm_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = exp_approx(*v));
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn sigmoid(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm_storeu_ps(c.as_mut_ptr(), sigmoid4(_mm_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = sigmoid_approx(*v));
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn tanh(x: &mut [T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm_storeu_ps(c.as_mut_ptr(), tanh4(_mm_loadu_ps(c.as_ptr())));
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|v| *v = tanh_approx(*v));
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn add(x: &mut [T], y: &[T]) {
+        let mut chunks = x.chunks_exact_mut(LANES);
+        let mut y_chunks = y.chunks_exact(LANES);
+        for (c, d) in (&mut chunks).zip(&mut y_chunks) {
+            let sum = _mm_add_ps(_mm_loadu_ps(c.as_ptr()), _mm_loadu_ps(d.as_ptr()));
+            _mm_storeu_ps(c.as_mut_ptr(), sum);
+        }
+        for (a, b) in chunks.into_remainder().iter_mut().zip(y_chunks.remainder()) {
+            *a += b;
+        }
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn add_scalar(x: &mut [T], s: T) {
+        let sv = _mm_set1_ps(s);
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm_storeu_ps(c.as_mut_ptr(), _mm_add_ps(_mm_loadu_ps(c.as_ptr()), sv));
+        }
+        chunks.into_remainder().iter_mut().for_each(|v| *v += s);
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn mul_scalar(x: &mut [T], s: T) {
+        let sv = _mm_set1_ps(s);
+        let mut chunks = x.chunks_exact_mut(LANES);
+        for c in &mut chunks {
+            _mm_storeu_ps(c.as_mut_ptr(), _mm_mul_ps(_mm_loadu_ps(c.as_ptr()), sv));
+        }
+        chunks.into_remainder().iter_mut().for_each(|v| *v *= s);
+    }
+}
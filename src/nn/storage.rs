@@ -0,0 +1,295 @@
+use super::{Activation, AsView, Float, Loss, NNArch, NNMatrix, T};
+use half::slice::HalfFloatSliceExt;
+use half::{bf16, f16};
+use std::fmt;
+
+/// compact element type of a matrix, the values are widened to f32 for arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// ieee half precision, 11 bit mantissa and a range of about 6e-5..65504
+    F16,
+    /// bfloat16, the range of f32 with an 8 bit mantissa
+    Bf16,
+}
+
+impl Storage {
+    /// bits of the value nearest to v.
+    pub fn encode(self, v: T) -> u16 {
+        match self {
+            Storage::F16 => f16::from_f32(v).to_bits(),
+            Storage::Bf16 => bf16::from_f32(v).to_bits(),
+        }
+    }
+
+    /// value of bits, exact as every half value is a f32 value.
+    pub fn decode(self, bits: u16) -> T {
+        match self {
+            Storage::F16 => f16::from_bits(bits).to_f32(),
+            Storage::Bf16 => bf16::from_bits(bits).to_f32(),
+        }
+    }
+}
+
+impl fmt::Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Storage::F16 => write!(f, "f16"),
+            Storage::Bf16 => write!(f, "bf16"),
+        }
+    }
+}
+
+/// 16 bit element type of a NNMatrix, f16 or bf16. `NNMatrix<f16>` is a matrix stored in
+/// half the bytes of a `NNMatrix<f32>`, cast() converts between the two.
+pub trait Half: Float {
+    const STORAGE: Storage;
+
+    /// widen every value of src into out, uses the f16c instructions when the cpu has them.
+    fn widen_slice(src: &[Self], out: &mut [T]);
+}
+
+impl Half for f16 {
+    const STORAGE: Storage = Storage::F16;
+
+    fn widen_slice(src: &[f16], out: &mut [T]) {
+        src.convert_to_f32_slice(out);
+    }
+}
+
+impl Half for bf16 {
+    const STORAGE: Storage = Storage::Bf16;
+
+    fn widen_slice(src: &[bf16], out: &mut [T]) {
+        src.convert_to_f32_slice(out);
+    }
+}
+
+/// c = a * w in f32, one row of w is widened at a time so the whole matrix is never held
+/// in f32.
+fn matmul_widened<H: Half>(a: &NNMatrix, w: &NNMatrix<H>, c: &mut NNMatrix) {
+    assert!(a.cols == w.rows && c.rows == a.rows && c.cols == w.cols);
+    c.fill(0.0);
+    let mut row = vec![0.0; w.cols];
+    for k in 0..w.rows {
+        H::widen_slice(w.row(k), &mut row);
+        for i in 0..a.rows {
+            let s = a.get_at(i, k);
+            for (o, &v) in c.row_mut(i).iter_mut().zip(&row) {
+                *o += s * v;
+            }
+        }
+    }
+}
+
+/// inference only copy of a model with the weights and biases stored as H, f16 or bf16.
+/// the activations are computed in f32.
+#[derive(Debug, Clone)]
+pub struct HalfNN<H: Half = f16> {
+    pub layer_count: usize,
+    pub wl: Box<[NNMatrix<H>]>,
+    /// read with bl() and changed with set_bias() so biases stays in sync
+    bl: Box<[NNMatrix<H>]>,
+    pub activations: Box<[Activation]>,
+    pub loss: Loss,
+    /// bl widened to f32, forward_batch() adds them to every row
+    biases: Box<[NNMatrix]>,
+    /// activation layers, input first, resized to the batch
+    al: Box<[NNMatrix]>,
+    /// pre-activation layers, resized to the batch
+    zl: Box<[NNMatrix]>,
+}
+
+impl<F: Float> NNArch<F> {
+    /// inference copy of the model with the weights and biases rounded to H.
+    pub fn to_half<H: Half>(&self) -> HalfNN<H> {
+        HalfNN::from_layers(
+            self.wl.iter().map(|w| w.cast()).collect(),
+            self.bl.iter().map(|b| b.cast()).collect(),
+            self.activations.to_vec(),
+            self.loss,
+        )
+    }
+}
+
+impl<H: Half> HalfNN<H> {
+    /// layers stored as H, used by to_half() and the model file reader.
+    pub(super) fn from_layers(
+        wl: Vec<NNMatrix<H>>,
+        bl: Vec<NNMatrix<H>>,
+        activations: Vec<Activation>,
+        loss: Loss,
+    ) -> Self {
+        let mut arch = vec![wl[0].rows];
+        arch.extend(wl.iter().map(|w| w.cols));
+        HalfNN {
+            layer_count: wl.len(),
+            // every stored value is exact in f32, so decoding once loses nothing
+            biases: bl.iter().map(|b| b.cast()).collect(),
+            wl: wl.into_boxed_slice(),
+            bl: bl.into_boxed_slice(),
+            activations: activations.into_boxed_slice(),
+            loss,
+            al: arch.iter().map(|&n| NNMatrix::empty(1, n)).collect(),
+            zl: arch[1..].iter().map(|&n| NNMatrix::empty(1, n)).collect(),
+        }
+    }
+
+    /// f16 or bf16, the element type of the weights and biases.
+    pub fn storage(&self) -> Storage {
+        H::STORAGE
+    }
+
+    /// the bias row of every layer.
+    pub fn bl(&self) -> &[NNMatrix<H>] {
+        &self.bl
+    }
+
+    /// replace the bias row of layer, which needs the shape of the old one.
+    pub fn set_bias(&mut self, layer: usize, bias: NNMatrix<H>) {
+        let old = &self.bl[layer];
+        assert!(
+            (bias.rows, bias.cols) == (old.rows, old.cols),
+            "layer {layer} needs a {}x{} bias",
+            old.rows,
+            old.cols
+        );
+        self.biases[layer] = bias.cast();
+        self.bl[layer] = bias;
+    }
+
+    /// the layer sizes, input layer first.
+    pub fn arch(&self) -> Vec<usize> {
+        self.al.iter().map(|a| a.cols).collect()
+    }
+
+    /// full precision model with the stored values widened to F.
+    pub fn to_arch<F: Float>(&self) -> NNArch<F> {
+        NNArch::from_layers(
+            self.wl.iter().map(|w| w.cast()).collect(),
+            self.bl.iter().map(|b| b.cast()).collect(),
+            &self.activations,
+            self.loss,
+        )
+    }
+
+    /// bytes taken by the weights and biases.
+    pub fn param_bytes(&self) -> usize {
+        let values: usize = self
+            .wl
+            .iter()
+            .chain(self.bl.iter())
+            .map(|m| m.rows * m.cols)
+            .sum();
+        values * std::mem::size_of::<H>()
+    }
+
+    /// NNArch::forward_batch() with the weights widened to f32 one row at a time.
+    pub fn forward_batch(&mut self, input: &(impl AsView<Elem = T> + ?Sized)) -> &NNMatrix {
+        let input = input.as_view();
+        assert!(input.cols == self.al[0].cols);
+        for a in self.al.iter_mut().chain(self.zl.iter_mut()) {
+            if a.rows != input.rows {
+                *a = NNMatrix::empty(input.rows, a.cols);
+            }
+        }
+        for i in 0..input.rows {
+            self.al[0].copy_row(i, &input, i);
+        }
+        for i in 0..self.layer_count {
+            matmul_widened(&self.al[i], &self.wl[i], &mut self.zl[i]);
+            self.zl[i] += &self.biases[i];
+            self.activations[i].forward(&self.zl[i], &mut self.al[i + 1]);
+        }
+        &self.al[self.layer_count]
+    }
+
+    /// NNArch::cost() of the stored model.
+    pub fn cost(
+        &mut self,
+        df_input: &(impl AsView<Elem = T> + ?Sized),
+        df_output: &(impl AsView<Elem = T> + ?Sized),
+    ) -> T {
+        let df_input = df_input.as_view();
+        let df_output = df_output.as_view();
+        assert!(df_input.rows == df_output.rows);
+        let last = self.layer_count;
+        let fused = self.loss.fuses_with(self.activations[last - 1]);
+        let mut cost = 0.0;
+        self.forward_batch(&df_input);
+        for i in 0..df_input.rows {
+            let output = df_output.row(i);
+            if fused {
+                cost += self.loss.fused_value(&self.zl[last - 1].get_row(i), output);
+            } else {
+                cost += self.loss.value(&self.al[last].get_row(i), output);
+            }
+        }
+        cost / df_input.rows as T
+    }
+
+    /// compare the outputs and the cost of this model with the f32 model it was made from.
+    pub fn report(
+        &mut self,
+        model: &mut NNArch,
+        df_input: &(impl AsView<Elem = T> + ?Sized),
+        df_output: &(impl AsView<Elem = T> + ?Sized),
+    ) -> HalfReport {
+        let expected = model.forward_batch(df_input).clone();
+        let actual = self.forward_batch(df_input);
+        let mut max_abs: T = 0.0;
+        let mut sum_abs: T = 0.0;
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            let diff = (a - e).abs();
+            max_abs = max_abs.max(diff);
+            sum_abs += diff;
+        }
+        let values = (expected.rows * expected.cols).max(1);
+        let param_bytes = self.param_bytes();
+        HalfReport {
+            storage: H::STORAGE,
+            max_abs,
+            mean_abs: sum_abs / values as T,
+            cost: model.cost(df_input, df_output),
+            half_cost: self.cost(df_input, df_output),
+            bytes: param_bytes * std::mem::size_of::<T>() / std::mem::size_of::<H>(),
+            half_bytes: param_bytes,
+        }
+    }
+}
+
+/// difference between a HalfNN and the f32 model it was made from on the same data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalfReport {
+    pub storage: Storage,
+    /// largest absolute difference of an output value
+    pub max_abs: T,
+    /// mean absolute difference of the output values
+    pub mean_abs: T,
+    /// cost of the f32 model
+    pub cost: T,
+    /// cost of the half model
+    pub half_cost: T,
+    /// size of the f32 weights and biases
+    pub bytes: usize,
+    /// size of the half weights and biases
+    pub half_bytes: usize,
+}
+
+impl fmt::Display for HalfReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: output max abs {:.3e} mean abs {:.3e}",
+            self.storage, self.max_abs, self.mean_abs
+        )?;
+        writeln!(
+            f,
+            "cost f32 {:.6} {} {:.6} ({:+.3e})",
+            self.cost,
+            self.storage,
+            self.half_cost,
+            self.half_cost - self.cost
+        )?;
+        write!(f, "size {} -> {} bytes", self.bytes, self.half_bytes)
+    }
+}
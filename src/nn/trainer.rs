@@ -0,0 +1,166 @@
+use super::{AsView, Float, NNArch, NNMatrix, Optimizer};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// mini-batch gradient descent.
+/// every epoch the rows of the data are shuffled and split into batches of batch_size rows,
+/// the gradient of each batch is averaged by backprop and handed to the optimizer.
+/// a batch_size of 1 is stochastic gradient descent, a batch_size of all the rows is
+/// full batch gradient descent.
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    /// number of rows used for every step, the last batch of an epoch may be smaller
+    pub batch_size: usize,
+    /// shuffle the rows before every epoch
+    pub shuffle: bool,
+    /// number of epochs completed
+    pub epoch: usize,
+    /// number of optimizer steps taken
+    pub step: usize,
+    rng: StdRng,
+}
+
+impl Trainer {
+    pub fn new(batch_size: usize) -> Self {
+        assert!(batch_size > 0);
+        Trainer {
+            batch_size,
+            shuffle: true,
+            epoch: 0,
+            step: 0,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// shuffle with a fixed seed so the batches are the same on every run.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// keep the rows in order.
+    pub fn without_shuffle(mut self) -> Self {
+        self.shuffle = false;
+        self
+    }
+
+    /// number of steps in an epoch over rows samples.
+    pub fn steps_per_epoch(&self, rows: usize) -> usize {
+        rows.div_ceil(self.batch_size)
+    }
+
+    /// run one pass over the data, taking one optimizer step per batch.
+    pub fn train_epoch<F: Float>(
+        &mut self,
+        model: &mut NNArch<F>,
+        gradient: &mut NNArch<F>,
+        optimizer: &mut dyn Optimizer<F>,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+    ) {
+        let mut rng = self.rng.clone();
+        self.train_epoch_with(model, gradient, optimizer, df_input, df_output, &mut rng);
+        self.rng = rng;
+    }
+
+    /// train_epoch() shuffling with rng instead of the generator of the trainer, so one
+    /// seeded rng can drive the initialization, the split and the shuffling.
+    pub fn train_epoch_with<F: Float, R: Rng + ?Sized>(
+        &mut self,
+        model: &mut NNArch<F>,
+        gradient: &mut NNArch<F>,
+        optimizer: &mut dyn Optimizer<F>,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+        rng: &mut R,
+    ) {
+        let df_input = df_input.as_view();
+        let df_output = df_output.as_view();
+        assert!(df_input.rows == df_output.rows);
+        let mut order: Vec<usize> = (0..df_input.rows).collect();
+        if self.shuffle {
+            order.shuffle(rng);
+        }
+
+        for batch in order.chunks(self.batch_size) {
+            if self.shuffle {
+                let batch_input = df_input.select_rows(batch);
+                let batch_output = df_output.select_rows(batch);
+                model.backprop(gradient, &batch_input, &batch_output);
+            } else {
+                // the rows are in order so the batch is borrowed instead of copied
+                let rows = batch[0]..batch[0] + batch.len();
+                let batch_input = df_input.rows_view(rows.clone());
+                let batch_output = df_output.rows_view(rows);
+                model.backprop(gradient, &batch_input, &batch_output);
+            }
+            optimizer.step(model, gradient);
+            self.step += 1;
+        }
+        self.epoch += 1;
+    }
+
+    /// run epochs passes over the data and return the cost over all of it after each one.
+    pub fn train<F: Float>(
+        &mut self,
+        model: &mut NNArch<F>,
+        gradient: &mut NNArch<F>,
+        optimizer: &mut dyn Optimizer<F>,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+        epochs: usize,
+    ) -> Vec<F> {
+        let mut rng = self.rng.clone();
+        let history = self.train_with(
+            model, gradient, optimizer, df_input, df_output, epochs, &mut rng,
+        );
+        self.rng = rng;
+        history
+    }
+
+    /// train() shuffling with rng instead of the generator of the trainer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn train_with<F: Float, R: Rng + ?Sized>(
+        &mut self,
+        model: &mut NNArch<F>,
+        gradient: &mut NNArch<F>,
+        optimizer: &mut dyn Optimizer<F>,
+        df_input: &(impl AsView<Elem = F> + ?Sized),
+        df_output: &(impl AsView<Elem = F> + ?Sized),
+        epochs: usize,
+        rng: &mut R,
+    ) -> Vec<F> {
+        let mut history: Vec<F> = Vec::with_capacity(epochs);
+        for _ in 0..epochs {
+            self.train_epoch_with(model, gradient, optimizer, df_input, df_output, rng);
+            history.push(model.cost(df_input, df_output));
+        }
+        history
+    }
+}
+
+/// shuffle the rows with rng and split them into a train and a test set, test_ratio of the
+/// rows (rounded to the nearest row) go into the test set.
+/// returns (train_input, train_output, test_input, test_output).
+pub fn train_test_split<F: Float, R: Rng + ?Sized>(
+    df_input: &(impl AsView<Elem = F> + ?Sized),
+    df_output: &(impl AsView<Elem = F> + ?Sized),
+    test_ratio: f64,
+    rng: &mut R,
+) -> (NNMatrix<F>, NNMatrix<F>, NNMatrix<F>, NNMatrix<F>) {
+    let df_input = df_input.as_view();
+    let df_output = df_output.as_view();
+    assert!(df_input.rows == df_output.rows);
+    assert!((0.0..=1.0).contains(&test_ratio));
+    let mut order: Vec<usize> = (0..df_input.rows).collect();
+    order.shuffle(rng);
+    let test_rows = (df_input.rows as f64 * test_ratio).round() as usize;
+    let (test, train) = order.split_at(test_rows);
+    (
+        df_input.select_rows(train),
+        df_output.select_rows(train),
+        df_input.select_rows(test),
+        df_output.select_rows(test),
+    )
+}
@@ -0,0 +1,423 @@
+use super::{
+    broadcast_shape, check_bounds, check_broadcast, check_layout, check_row, check_shape,
+    try_matmul_into, Float, MatrixError, NNMatrix, T,
+};
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+
+/// anything that can be borrowed as a read only matrix, lets the functions taking data accept
+/// an owned NNMatrix and a view alike.
+pub trait AsView {
+    type Elem: Float;
+    fn as_view(&self) -> NNMatrixView<'_, Self::Elem>;
+}
+
+/// borrowed rows x cols window into a buffer, row i starts at i * stride.
+/// creating and slicing a view never copies the data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NNMatrixView<'a, F: Float = T> {
+    data: &'a [F],
+    pub rows: usize,
+    pub cols: usize,
+    pub stride: usize,
+}
+
+/// mutable version of NNMatrixView.
+#[derive(Debug, PartialEq)]
+pub struct NNMatrixViewMut<'a, F: Float = T> {
+    data: &'a mut [F],
+    pub rows: usize,
+    pub cols: usize,
+    pub stride: usize,
+}
+
+/// offset and length of the part of the buffer covered by the window rows x cols.
+fn window(stride: usize, rows: &Range<usize>, cols: &Range<usize>) -> (usize, usize) {
+    if rows.is_empty() {
+        return (0, 0);
+    }
+    let offset = rows.start * stride + cols.start;
+    (offset, (rows.len() - 1) * stride + cols.len())
+}
+
+/// panic unless range is a valid range of 0..len.
+fn check_range(what: &str, range: &Range<usize>, len: usize) {
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "{what} {}..{} is out of bounds of {len} {what}",
+        range.start,
+        range.end
+    );
+}
+
+impl<'a, F: Float> NNMatrixView<'a, F> {
+    /// view data as a rows x cols matrix with the given stride, see NNMatrix::new().
+    pub fn new(data: &'a [F], rows: usize, cols: usize, stride: usize) -> Self {
+        check_layout(data.len(), rows, cols, stride);
+        NNMatrixView {
+            data,
+            rows,
+            cols,
+            stride,
+        }
+    }
+
+    pub fn get_at(&self, row: usize, col: usize) -> F {
+        self.try_get(row, col).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// get_at() that returns an error instead of panicking.
+    pub fn try_get(&self, row: usize, col: usize) -> Result<F, MatrixError> {
+        check_bounds(row, col, (self.rows, self.cols))?;
+        Ok(self.data[row * self.stride + col])
+    }
+
+    /// value at row, col without the bounds check.
+    ///
+    /// # Safety
+    /// row < rows, col < cols and row * stride + col is inside data, the public fields can
+    /// be changed so the shape alone does not prove it.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, row: usize, col: usize) -> F {
+        debug_assert!(row < self.rows && col < self.cols);
+        *self.data.get_unchecked(row * self.stride + col)
+    }
+
+    /// the cols values of row as a slice of the underlying buffer.
+    pub fn row(&self, row: usize) -> &'a [F] {
+        check_row(row, (self.rows, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        let start = row * self.stride;
+        &self.data[start..start + self.cols]
+    }
+
+    /// every row as a slice, top to bottom.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &'a [F]> + DoubleEndedIterator {
+        let view = *self;
+        (0..view.rows).map(move |i| view.row(i))
+    }
+
+    /// every element row by row, skipping the gap between rows when stride > cols.
+    pub fn iter(&self) -> impl Iterator<Item = &'a F> {
+        self.rows().flatten()
+    }
+
+    /// every element of column col, top to bottom. panics unless col < cols.
+    pub fn col_iter(&self, col: usize) -> impl ExactSizeIterator<Item = &'a F> {
+        check_bounds(0, col, (1, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        let view = *self;
+        (0..view.rows).map(move |i| &view.data[i * view.stride + col])
+    }
+
+    /// iter() paired with the (row, col) of every element.
+    pub fn enumerate_indexed(&self) -> impl Iterator<Item = ((usize, usize), &'a F)> {
+        self.rows()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, v)| ((i, j), v)))
+    }
+
+    /// the rows x cols window of the view, indices are relative to the view.
+    pub fn submatrix(&self, rows: Range<usize>, cols: Range<usize>) -> NNMatrixView<'a, F> {
+        check_range("rows", &rows, self.rows);
+        check_range("cols", &cols, self.cols);
+        let (offset, len) = window(self.stride, &rows, &cols);
+        NNMatrixView {
+            data: &self.data[offset..offset + len],
+            rows: rows.len(),
+            cols: cols.len(),
+            stride: self.stride,
+        }
+    }
+
+    /// the given rows with every column.
+    pub fn rows_view(&self, rows: Range<usize>) -> NNMatrixView<'a, F> {
+        self.submatrix(rows, 0..self.cols)
+    }
+
+    /// the given columns of every row.
+    pub fn cols_view(&self, cols: Range<usize>) -> NNMatrixView<'a, F> {
+        self.submatrix(0..self.rows, cols)
+    }
+
+    /// a single row as a 1 x cols view.
+    pub fn row_view(&self, row: usize) -> NNMatrixView<'a, F> {
+        self.rows_view(row..row + 1)
+    }
+
+    /// the rows before at and the rows from at onwards, eg. for an unshuffled train/test split.
+    pub fn split_rows(&self, at: usize) -> (NNMatrixView<'a, F>, NNMatrixView<'a, F>) {
+        (self.rows_view(0..at), self.rows_view(at..self.rows))
+    }
+
+    /// copy the view into a new contiguous matrix.
+    pub fn to_matrix(&self) -> NNMatrix<F> {
+        let mut m = NNMatrix::empty(self.rows, self.cols);
+        for (to, from) in m.rows_mut().zip(self.rows()) {
+            to.copy_from_slice(from);
+        }
+        m
+    }
+
+    /// create a new matrix out of the given rows, in the given order.
+    pub fn select_rows(&self, rows: &[usize]) -> NNMatrix<F> {
+        let mut m = NNMatrix::empty(rows.len(), self.cols);
+        for (to, &row) in m.rows_mut().zip(rows) {
+            to.copy_from_slice(self.row(row));
+        }
+        m
+    }
+
+    /// dot product self * b, b needs self.cols rows.
+    pub fn try_matmul(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        let mut c = NNMatrix::empty(self.rows, b.cols);
+        try_matmul_into(self, b, &mut c.view_mut())?;
+        Ok(c)
+    }
+
+    /// element wise self + b, broadcasting rows, columns and 1 x 1 matrices.
+    pub fn try_add(&self, b: &NNMatrixView<F>) -> Result<NNMatrix<F>, MatrixError> {
+        let shape = broadcast_shape("add", (self.rows, self.cols), (b.rows, b.cols))?;
+        let mut c = self.expand(shape);
+        c.view_mut().try_add_assign(b)?;
+        Ok(c)
+    }
+}
+
+impl<'a, F: Float> NNMatrixViewMut<'a, F> {
+    /// mutably view data as a rows x cols matrix with the given stride, see NNMatrix::new().
+    pub fn new(data: &'a mut [F], rows: usize, cols: usize, stride: usize) -> Self {
+        check_layout(data.len(), rows, cols, stride);
+        NNMatrixViewMut {
+            data,
+            rows,
+            cols,
+            stride,
+        }
+    }
+
+    /// read only view of the same window.
+    pub fn view(&self) -> NNMatrixView<'_, F> {
+        NNMatrixView {
+            data: self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+        }
+    }
+
+    pub fn get_at(&self, row: usize, col: usize) -> F {
+        self.view().get_at(row, col)
+    }
+
+    pub fn get_mut_at(&mut self, row: usize, col: usize) -> &mut F {
+        self.try_get_mut(row, col).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn set_at(&mut self, row: usize, col: usize, value: F) {
+        *self.get_mut_at(row, col) = value;
+    }
+
+    /// get_mut_at() that returns an error instead of panicking.
+    pub fn try_get_mut(&mut self, row: usize, col: usize) -> Result<&mut F, MatrixError> {
+        check_bounds(row, col, (self.rows, self.cols))?;
+        Ok(&mut self.data[row * self.stride + col])
+    }
+
+    /// the cols values of row as a mutable slice of the underlying buffer.
+    pub fn row_mut(&mut self, row: usize) -> &mut [F] {
+        check_row(row, (self.rows, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        let start = row * self.stride;
+        &mut self.data[start..start + self.cols]
+    }
+
+    /// every row as a mutable slice, top to bottom.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [F]> {
+        NNMatrixViewMut {
+            data: &mut *self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+        }
+        .into_rows_mut()
+    }
+
+    /// rows_mut() consuming the view so the rows keep the lifetime of the buffer.
+    pub fn into_rows_mut(self) -> impl Iterator<Item = &'a mut [F]> {
+        let (rows, cols, stride) = (self.rows, self.cols, self.stride);
+        let mut rest = self.data;
+        (0..rows).map(move |i| {
+            let (row, tail) = std::mem::take(&mut rest).split_at_mut(cols);
+            // the last row can end before a full stride
+            if i + 1 < rows {
+                rest = &mut tail[stride - cols..];
+            }
+            row
+        })
+    }
+
+    /// every element row by row, skipping the gap between rows when stride > cols.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut F> {
+        self.rows_mut().flatten()
+    }
+
+    /// the mutable rows x cols window of the view, indices are relative to the view.
+    pub fn submatrix_mut(
+        &mut self,
+        rows: Range<usize>,
+        cols: Range<usize>,
+    ) -> NNMatrixViewMut<'_, F> {
+        NNMatrixViewMut {
+            data: &mut *self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+        }
+        .into_submatrix(rows, cols)
+    }
+
+    /// submatrix_mut() consuming the view so the window keeps the lifetime of the buffer.
+    pub fn into_submatrix(self, rows: Range<usize>, cols: Range<usize>) -> NNMatrixViewMut<'a, F> {
+        check_range("rows", &rows, self.rows);
+        check_range("cols", &cols, self.cols);
+        let (offset, len) = window(self.stride, &rows, &cols);
+        NNMatrixViewMut {
+            data: &mut self.data[offset..offset + len],
+            rows: rows.len(),
+            cols: cols.len(),
+            stride: self.stride,
+        }
+    }
+
+    /// the rows before at and the rows from at onwards as two views that can be written
+    /// at the same time, eg. from two threads.
+    pub fn split_rows_mut(self, at: usize) -> (NNMatrixViewMut<'a, F>, NNMatrixViewMut<'a, F>) {
+        check_range("rows", &(0..at), self.rows);
+        let split = (at * self.stride).min(self.data.len());
+        let (top, bottom) = self.data.split_at_mut(split);
+        let top = NNMatrixViewMut {
+            data: top,
+            rows: at,
+            cols: self.cols,
+            stride: self.stride,
+        };
+        let bottom = NNMatrixViewMut {
+            data: bottom,
+            rows: self.rows - at,
+            cols: self.cols,
+            stride: self.stride,
+        };
+        (top, bottom)
+    }
+
+    /// the given rows with every column.
+    pub fn rows_view_mut(&mut self, rows: Range<usize>) -> NNMatrixViewMut<'_, F> {
+        let cols = self.cols;
+        self.submatrix_mut(rows, 0..cols)
+    }
+
+    /// the given columns of every row.
+    pub fn cols_view_mut(&mut self, cols: Range<usize>) -> NNMatrixViewMut<'_, F> {
+        let rows = self.rows;
+        self.submatrix_mut(0..rows, cols)
+    }
+
+    /// set every element of the view to value.
+    pub fn fill(&mut self, value: F) {
+        self.for_each_slice(|s| s.fill(value));
+    }
+
+    /// run f over every element, in one call when the rows are contiguous and once per row
+    /// otherwise, for the simd kernels.
+    pub(super) fn for_each_slice<K: FnMut(&mut [F])>(&mut self, mut f: K) {
+        if self.stride == self.cols {
+            f(&mut self.data[..self.rows * self.cols]);
+        } else {
+            self.rows_mut().for_each(f);
+        }
+    }
+
+    /// copy every element of from into the view, from needs the same shape.
+    pub fn try_copy_from(&mut self, from: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        check_shape("copy_from", (self.rows, self.cols), (from.rows, from.cols))?;
+        for (to, from) in self.rows_mut().zip(from.rows()) {
+            to.copy_from_slice(from);
+        }
+        Ok(())
+    }
+
+    /// element wise self += b, b can be a row, a column or 1 x 1 and is repeated to the
+    /// shape of self.
+    pub fn try_add_assign(&mut self, b: &NNMatrixView<F>) -> Result<(), MatrixError> {
+        check_broadcast("add_assign", (self.rows, self.cols), (b.rows, b.cols))?;
+        for i in 0..self.rows {
+            let row = b.row(if b.rows == 1 { 0 } else { i });
+            if b.cols == self.cols {
+                F::add_slice(self.row_mut(i), row);
+            } else {
+                F::add_scalar_slice(self.row_mut(i), row[0]);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ====================== as view start ==================================== //
+impl<F: Float> AsView for NNMatrix<F> {
+    type Elem = F;
+    fn as_view(&self) -> NNMatrixView<'_, F> {
+        self.view()
+    }
+}
+
+impl<F: Float> AsView for NNMatrixView<'_, F> {
+    type Elem = F;
+    fn as_view(&self) -> NNMatrixView<'_, F> {
+        *self
+    }
+}
+
+impl<F: Float> AsView for NNMatrixViewMut<'_, F> {
+    type Elem = F;
+    fn as_view(&self) -> NNMatrixView<'_, F> {
+        self.view()
+    }
+}
+// ====================== as view end ==================================== //
+
+// ====================== index start ==================================== //
+impl<F: Float> Index<(usize, usize)> for NNMatrixView<'_, F> {
+    type Output = F;
+    fn index(&self, (row, col): (usize, usize)) -> &F {
+        check_bounds(row, col, (self.rows, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        &self.data[row * self.stride + col]
+    }
+}
+
+impl<F: Float> Index<(usize, usize)> for NNMatrixViewMut<'_, F> {
+    type Output = F;
+    fn index(&self, (row, col): (usize, usize)) -> &F {
+        check_bounds(row, col, (self.rows, self.cols)).unwrap_or_else(|e| panic!("{e}"));
+        &self.data[row * self.stride + col]
+    }
+}
+
+impl<F: Float> IndexMut<(usize, usize)> for NNMatrixViewMut<'_, F> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut F {
+        self.get_mut_at(row, col)
+    }
+}
+// ====================== index end ==================================== //
+
+impl<F: Float> fmt::Display for NNMatrixView<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(f)?;
+        for row in self.rows() {
+            for (col, num) in row.iter().enumerate() {
+                let prefix = if col == 0 { "  |" } else { "," };
+                let postfix = if col + 1 == self.cols { "|" } else { "" };
+                write!(f, "{prefix}{num:-9.6}{postfix}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
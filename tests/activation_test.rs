@@ -0,0 +1,73 @@
+#[cfg(test)]
+pub mod activation_tests {
+    use mm_nn::nn::{gradcheck, Activation, NNArch, NNMatrix, T};
+
+    const ELEMENTWISE: [Activation; 8] = [
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::Relu,
+        Activation::LeakyRelu(0.1),
+        Activation::Elu(1.0),
+        Activation::Gelu,
+        Activation::Softplus,
+        Activation::Identity,
+    ];
+
+    #[test]
+    fn derivative_matches_slope() {
+        let eps: T = 1e-3;
+        for act in ELEMENTWISE {
+            for z in [-2.0, -0.5, 0.3, 1.7] {
+                let numeric = (act.apply(z + eps) - act.apply(z - eps)) / (2.0 * eps);
+                let analytic = act.derivative(z, act.apply(z));
+                assert!(
+                    (numeric - analytic).abs() < 1e-2,
+                    "{act:?} at {z}: {numeric} vs {analytic}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn softmax_rows_sum_to_one() {
+        let z = NNMatrix::new(Some(&[1.0, 2.0, 3.0, 1000.0, 0.0, -1000.0]), 2, 3, 3);
+        let mut a = NNMatrix::empty(2, 3);
+        Activation::Softmax.forward(&z, &mut a);
+        for i in 0..2 {
+            let sum: T = a.get_row(i).iter().sum();
+            assert!((sum - 1.0).abs() < 1e-6);
+        }
+        assert!(a.get_at(0, 2) > a.get_at(0, 1) && a.get_at(0, 1) > a.get_at(0, 0));
+        assert_eq!(a.get_at(1, 0), 1.0);
+    }
+
+    #[test]
+    fn apply_row_normalizes_softmax() {
+        let mut a: [T; 3] = [0.0; 3];
+        Activation::Softmax.apply_row(&[1.0, 2.0, 3.0], &mut a);
+        assert!((a.iter().sum::<T>() - 1.0).abs() < 1e-6);
+        let mut b: [T; 3] = [0.0; 3];
+        Activation::Tanh.apply_row(&[1.0, 2.0, 3.0], &mut b);
+        assert_eq!(b[1], Activation::Tanh.apply(2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "softmax needs the whole row")]
+    fn softmax_apply_panics() {
+        Activation::Softmax.apply(1.0 as T);
+    }
+
+    #[test]
+    fn gradcheck_mixed_activations() {
+        let td: [T; 12] = [0.1, 0.9, 0.2, 0.8, 0.7, 0.3, 0.4, 0.6, 0.1, 0.3, 0.5, 0.7];
+        let df_input = NNMatrix::new(Some(&td[..]), 3, 2, 4);
+        let df_output = NNMatrix::new(Some(&td[2..]), 3, 2, 4);
+
+        for hidden in [Activation::Tanh, Activation::Gelu, Activation::Softplus] {
+            let mut model = NNArch::create(&[2, 3, 2], &[hidden, Activation::Softmax]).with_seed(5);
+            model.randomize();
+            let report = gradcheck(&mut model, &df_input, &df_output, 1e-2);
+            assert!(report.max_abs() < 1e-3, "{hidden:?}: {report}");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+pub mod algebra_tests {
+    use mm_nn::nn::{MatrixError, NNMatrix, T};
+
+    fn m(rows: usize, cols: usize, values: &[T]) -> NNMatrix {
+        NNMatrix::new(Some(values), rows, cols, cols)
+    }
+
+    #[test]
+    fn operators_accept_owned_borrowed_and_views() {
+        let a = m(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        let b = m(2, 2, &[2.0, 4.0, 8.0, 16.0]);
+        // b as the top left corner of a wider matrix
+        let wide = m(2, 3, &[2.0, 4.0, 0.0, 8.0, 16.0, 0.0]);
+        let bv = wide.cols_view(0..2);
+
+        let sum = m(2, 2, &[3.0, 6.0, 11.0, 20.0]);
+        assert_eq!(a.clone() + b.clone(), sum);
+        assert_eq!(&a + &b, sum);
+        assert_eq!(a.view() + bv, sum);
+        assert_eq!(a.clone() + bv, sum);
+
+        let diff = m(2, 2, &[-1.0, -2.0, -5.0, -12.0]);
+        assert_eq!(a.clone() - b.clone(), diff);
+        assert_eq!(&a - bv, diff);
+        assert_eq!(a.view() - &b, diff);
+        assert_eq!(-(&b - &a), diff);
+        assert_eq!(-(b.view()) + a.view(), diff);
+
+        let quot = m(2, 2, &[0.5, 0.5, 0.375, 0.25]);
+        assert_eq!(a.clone() / b.clone(), quot);
+        assert_eq!(a.view() / bv, quot);
+
+        // Mul of two matrices stays the dot product
+        let prod = m(2, 2, &[18.0, 36.0, 38.0, 76.0]);
+        assert_eq!(a.clone() * b.clone(), prod);
+        assert_eq!(a.view() * bv, prod);
+        assert_eq!(&a * b, prod);
+    }
+
+    #[test]
+    fn scalar_and_assign_operators() {
+        let a = m(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(&a - 1.0, m(2, 2, &[0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(a.clone() / 2.0, m(2, 2, &[0.5, 1.0, 1.5, 2.0]));
+        assert_eq!(a.view() * 2.0, m(2, 2, &[2.0, 4.0, 6.0, 8.0]));
+
+        let mut c = a.clone();
+        c -= &a;
+        assert_eq!(c, NNMatrix::empty(2, 2));
+        c += a.view();
+        c /= &a;
+        assert_eq!(c, m(2, 2, &[1.0; 4]));
+
+        // assigning through a view only touches its window
+        let mut wide = m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut right = wide.submatrix_mut(0..2, 1..3);
+        right -= 1.0;
+        right /= 2.0;
+        right *= m(2, 2, &[1.0, 0.0, 0.0, 2.0]);
+        assert_eq!(wide, m(2, 3, &[1.0, 0.5, 2.0, 4.0, 2.0, 5.0]));
+    }
+
+    #[test]
+    fn linear_algebra() {
+        let a = m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let b = m(
+            4,
+            3,
+            &[1.0, 0.0, 1.0, 0.5, 1.0, -1.0, 2.0, 2.0, 2.0, 0.0, 0.0, 1.0],
+        );
+        assert_eq!(a.t(), m(3, 2, &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]));
+        assert_eq!(a.t().t(), a);
+        assert_eq!(a.matmul_transposed(&b), &a * &b.t());
+        assert_eq!(
+            a.rows_view(1..2).matmul_transposed(&b.view()),
+            m(1, 4, &[10.0, 1.0, 30.0, 6.0])
+        );
+
+        let x = m(1, 2, &[1.0, 2.0]);
+        let y = m(1, 3, &[3.0, 4.0, 5.0]);
+        assert_eq!(x.outer(&y), &x.t() * &y);
+        assert_eq!(a.hadamard(&a), a.map(|v| v * v));
+        assert_eq!(a.zip_map(&a, |u, v| u - v), NNMatrix::empty(2, 3));
+        assert_eq!(a.clamp(2.0, 5.0), m(2, 3, &[2.0, 2.0, 3.0, 4.0, 5.0, 5.0]));
+        assert_eq!(m(2, 2, &[3.0, 0.0, 0.0, 4.0]).norm(), 5.0);
+
+        let mut c = a.clone();
+        c.apply(|v| v * 10.0);
+        assert_eq!(c, &a * 10.0);
+        c.hadamard_assign(&a.map(|v| 1.0 / v));
+        assert!((c.mean() - 10.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reductions() {
+        let a = m(2, 3, &[1.0, 7.0, 3.0, 4.0, -5.0, 7.0]);
+        assert_eq!(a.sum_rows(), m(1, 3, &[5.0, 2.0, 10.0]));
+        assert_eq!(a.sum_cols(), m(2, 1, &[11.0, 6.0]));
+        assert_eq!(a.sum(), 17.0);
+        assert!((a.mean() - 17.0 / 6.0).abs() < 1e-6);
+        assert_eq!(a.max(), 7.0);
+        assert_eq!(a.argmax(), Some((0, 1)));
+        assert_eq!(a.argmax_rows(), vec![1, 2]);
+        assert_eq!(a.cols_view(0..1).argmax(), Some((1, 0)));
+        assert_eq!(NNMatrix::<T>::empty(0, 3).argmax(), None);
+    }
+
+    #[test]
+    fn shape_errors() {
+        let a = m(2, 2, &[1.0; 4]);
+        let b = m(2, 3, &[1.0; 6]);
+        assert_eq!(
+            a.try_sub(&b),
+            Err(MatrixError::Broadcast {
+                op: "sub",
+                lhs: (2, 2),
+                rhs: (2, 3),
+            })
+        );
+        assert!(a.try_div(&b).is_err());
+        assert!(a.try_hadamard(&b).is_err());
+        assert!(a.try_matmul_transposed(&b).is_err());
+        assert!(b.try_matmul_transposed(&a).is_err());
+        assert_eq!(
+            a.try_outer(&b),
+            Err(MatrixError::ShapeMismatch {
+                op: "outer",
+                expected: (1, 2),
+                actual: (2, 2),
+            })
+        );
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+pub mod autodiff_tests {
+    use mm_nn::nn::{Activation, Initializer, Loss, NNArch, NNMatrix, Tape, T};
+
+    fn m(rows: usize, cols: usize, values: &[T]) -> NNMatrix {
+        NNMatrix::new(Some(values), rows, cols, cols)
+    }
+
+    fn assert_close(a: &NNMatrix, b: &NNMatrix, tol: T) {
+        assert_eq!((a.rows, a.cols), (b.rows, b.cols));
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < tol, "{a}\n{b}");
+        }
+    }
+
+    fn data() -> (NNMatrix, NNMatrix) {
+        let input = m(
+            4,
+            3,
+            &[0.1, 0.9, 0.3, 0.5, 0.2, 0.8, 0.7, 0.4, 0.6, 0.0, 1.0, 0.2],
+        );
+        let output = m(4, 2, &[1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0]);
+        (input, output)
+    }
+
+    fn compare_with_backprop(activations: &[Activation], loss: Loss) {
+        let (input, output) = data();
+        let mut model = NNArch::create(&[3, 5, 2], activations)
+            .with_loss(loss)
+            .with_seed(4);
+        model.initialize(Initializer::XavierUniform, Initializer::Constant(0.1));
+
+        let mut tape = NNArch::create(&[3, 5, 2], activations);
+        let mut analytic = NNArch::create(&[3, 5, 2], activations);
+        model.tape_backprop(&mut tape, &input, &output);
+        model.backprop(&mut analytic, &input, &output);
+        for (t, a) in tape.params().into_iter().zip(analytic.params()) {
+            assert_close(t, a, 1e-5);
+        }
+    }
+
+    #[test]
+    fn tape_matches_backprop() {
+        compare_with_backprop(&[Activation::Tanh, Activation::Sigmoid], Loss::Mse);
+        compare_with_backprop(
+            &[Activation::Relu, Activation::Softmax],
+            Loss::CategoricalCrossEntropy,
+        );
+        compare_with_backprop(
+            &[Activation::Gelu, Activation::Sigmoid],
+            Loss::BinaryCrossEntropy,
+        );
+    }
+
+    #[test]
+    fn custom_graph_matches_finite_differences() {
+        let x = m(2, 3, &[0.5, -1.0, 2.0, 1.5, 0.3, -0.7]);
+        let w = m(3, 3, &[0.2, -0.4, 0.1, 0.7, 0.3, -0.2, -0.5, 0.6, 0.9]);
+        // a residual block with a tied weight, which the fixed dense stack can not express
+        let f = |w: &NNMatrix| -> (T, NNMatrix) {
+            let tape = Tape::new();
+            let (x, w) = (tape.var(&x), tape.var(w));
+            let h = (x * w).activation(Activation::Tanh) + x;
+            let y = (h * w.t() - x).hadamard(h).mean() * 3.0;
+            let value = y.value()[(0, 0)];
+            (value, y.backward()[w].clone())
+        };
+
+        let (_, grad) = f(&w);
+        let eps = 1e-2;
+        let mut numeric = NNMatrix::empty(3, 3);
+        for i in 0..3 {
+            for j in 0..3 {
+                let (mut up, mut down) = (w.clone(), w.clone());
+                up[(i, j)] += eps;
+                down[(i, j)] -= eps;
+                numeric[(i, j)] = (f(&up).0 - f(&down).0) / (2.0 * eps);
+            }
+        }
+        assert_close(&grad, &numeric, 1e-3);
+    }
+
+    #[test]
+    fn broadcast_gradients_are_summed() {
+        let tape = Tape::new();
+        let a = tape.var(&m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        let row = tape.var(&m(1, 3, &[1.0, 1.0, 1.0]));
+        let col = tape.var(&m(2, 1, &[2.0, 3.0]));
+        let unused = tape.var(&m(1, 1, &[0.0]));
+        let y = ((a + row) - col).sum_rows().sum();
+        assert_eq!(y.value()[(0, 0)], 12.0);
+
+        let grads = y.backward();
+        assert_eq!(grads[a], m(2, 3, &[1.0; 6]));
+        assert_eq!(grads[row], m(1, 3, &[2.0; 3]));
+        assert_eq!(grads[col], m(2, 1, &[-3.0; 2]));
+        assert!(grads.get(unused).is_none());
+        assert_eq!(tape.len(), 8);
+    }
+
+    #[test]
+    fn broadcast_on_either_side() {
+        let tape = Tape::new();
+        let a = tape.var(&m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        let row = tape.var(&m(1, 3, &[1.0, 2.0, 3.0]));
+        let col = tape.var(&m(2, 1, &[2.0, 3.0]));
+
+        let grads = (row + a).sum().backward();
+        assert_eq!(grads[row], m(1, 3, &[2.0; 3]));
+        assert_eq!(grads[a], m(2, 3, &[1.0; 6]));
+        let grads = (col - a).sum().backward();
+        assert_eq!(grads[col], m(2, 1, &[3.0; 2]));
+        assert_eq!(grads[a], m(2, 3, &[-1.0; 6]));
+
+        let grads = a.hadamard(row).sum().backward();
+        assert_eq!(grads[row], m(1, 3, &[5.0, 7.0, 9.0]));
+        assert_eq!(grads[a], m(2, 3, &[1.0, 2.0, 3.0, 1.0, 2.0, 3.0]));
+        let grads = col.hadamard(a).sum().backward();
+        assert_eq!(grads[col], m(2, 1, &[6.0, 15.0]));
+
+        // row reaches the sum through a broadcast hadamard and a broadcast add
+        let grads = (a.hadamard(row) + row).sum().backward();
+        assert_eq!(grads[row], m(1, 3, &[7.0, 9.0, 11.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "backward: expected a 1x1 var found 2x1")]
+    fn backward_needs_a_scalar() {
+        let tape = Tape::new();
+        let _ = tape.var(&m(2, 1, &[1.0, 2.0])).backward();
+    }
+}
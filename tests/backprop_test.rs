@@ -0,0 +1,54 @@
+#[cfg(test)]
+pub mod backprop_tests {
+    use mm_nn::nn::{Activation, NNArch, NNMatrix, T};
+
+    const XOR: [T; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+    #[test]
+    fn backprop_matches_finite_diff() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+        let layer_arch = [2, 3, 1];
+        let activations = [Activation::Sigmoid; 2];
+
+        let mut model = NNArch::create(&layer_arch[..], &activations).with_seed(1);
+        model.randomize();
+        let mut fd = NNArch::create(&layer_arch[..], &activations);
+        let mut bp = NNArch::create(&layer_arch[..], &activations);
+
+        model.finite_diff(&mut fd, &df_input, &df_output, 1e-3);
+        model.backprop(&mut bp, &df_input, &df_output);
+
+        for i in 0..model.layer_count {
+            for row in 0..fd.wl[i].rows {
+                for col in 0..fd.wl[i].cols {
+                    let diff = fd.wl[i].get_at(row, col) - bp.wl[i].get_at(row, col);
+                    assert!(diff.abs() < 1e-2, "wl{i}[{row}][{col}] differs by {diff}");
+                }
+            }
+            for col in 0..fd.bl[i].cols {
+                let diff = fd.bl[i].get_at(0, col) - bp.bl[i].get_at(0, col);
+                assert!(diff.abs() < 1e-2, "bl{i}[{col}] differs by {diff}");
+            }
+        }
+    }
+
+    #[test]
+    fn backprop_reduces_cost() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+        let layer_arch = [2, 2, 1];
+        let activations = [Activation::Sigmoid; 2];
+
+        let mut model = NNArch::create(&layer_arch[..], &activations).with_seed(2);
+        model.randomize();
+        let mut gradient = NNArch::create(&layer_arch[..], &activations);
+
+        let initial = model.cost(&df_input, &df_output);
+        for _ in 0..100 {
+            model.backprop(&mut gradient, &df_input, &df_output);
+            model.learn(&gradient, 1.0);
+        }
+        assert!(model.cost(&df_input, &df_output) < initial);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+pub mod bounds_tests {
+    use mm_nn::nn::NNMatrix;
+
+    #[test]
+    fn last_row_and_col_are_in_bounds() {
+        let mut m: NNMatrix = NNMatrix::empty(2, 3);
+        m.set_at(1, 2, 7.0);
+        m[(0, 2)] = 3.0;
+        *m.get_mut_at(1, 0) = 4.0;
+        assert_eq!(m.get_at(1, 2), 7.0);
+        assert_eq!(m[(0, 2)], 3.0);
+        assert_eq!(m[(1, 0)], 4.0);
+        assert_eq!(unsafe { m.get_unchecked(1, 2) }, 7.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "index (2, 0) is out of bounds of a 2x3 matrix")]
+    fn get_at_row_equal_to_rows_panics() {
+        NNMatrix::<f32>::empty(2, 3).get_at(2, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "index (0, 3) is out of bounds of a 2x3 matrix")]
+    fn set_at_col_equal_to_cols_panics() {
+        NNMatrix::<f32>::empty(2, 3).set_at(0, 3, 1.0);
+    }
+
+    #[test]
+    fn strided_matrix_skips_the_padding() {
+        // rows of 3 values where only the first 2 belong to the matrix
+        let td = [1.0, 2.0, 9.0, 3.0, 4.0, 9.0, 5.0, 6.0];
+        let m: NNMatrix = NNMatrix::new(Some(&td[..]), 3, 2, 3);
+        assert_eq!(m[(2, 1)], 6.0);
+        assert_eq!(m.get_row(1)[..], [3.0, 4.0]);
+        // one past the last col would read the padding, so it has to panic
+        assert!(m.try_get(0, 2).is_err());
+
+        let mut dst = NNMatrix::empty(3, 2);
+        dst.copy_row(2, &m, 1);
+        dst.copy_row_from(&m, 2);
+        assert_eq!(dst.get_row(0)[..], [5.0, 6.0]);
+        assert_eq!(dst.get_row(1)[..], [0.0, 0.0]);
+        assert_eq!(dst.get_row(2)[..], [3.0, 4.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 8 values found 7")]
+    fn new_checks_the_data_length() {
+        NNMatrix::<f32>::new(Some(&[0.0; 7]), 3, 2, 3);
+    }
+}
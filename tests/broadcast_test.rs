@@ -0,0 +1,82 @@
+#[cfg(test)]
+pub mod broadcast_tests {
+    use mm_nn::nn::{Activation, MatrixError, NNArch, NNMatrix, T};
+
+    fn m(rows: usize, cols: usize, values: &[T]) -> NNMatrix {
+        NNMatrix::new(Some(values), rows, cols, cols)
+    }
+
+    #[test]
+    fn rows_columns_and_scalars_broadcast() {
+        let a = m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let row = m(1, 3, &[10.0, 20.0, 30.0]);
+        let col = m(2, 1, &[1.0, 2.0]);
+        let one = m(1, 1, &[2.0]);
+
+        assert_eq!(&a + &row, m(2, 3, &[11.0, 22.0, 33.0, 14.0, 25.0, 36.0]));
+        assert_eq!(&row + &a, &a + &row);
+        assert_eq!(&a - &col, m(2, 3, &[0.0, 1.0, 2.0, 2.0, 3.0, 4.0]));
+        assert_eq!(&a / &one, &a / 2.0);
+        assert_eq!(a.hadamard(&col), m(2, 3, &[1.0, 2.0, 3.0, 8.0, 10.0, 12.0]));
+        // a column and a row broadcast to each other
+        assert_eq!(&col + &row, m(2, 3, &[11.0, 21.0, 31.0, 12.0, 22.0, 32.0]));
+        assert_eq!(col.view() - row.view(), -(&row - &col));
+    }
+
+    #[test]
+    fn assign_broadcasts_into_the_left_side() {
+        let mut a = m(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        a += m(1, 3, &[1.0, 1.0, 1.0]);
+        a -= m(2, 1, &[2.0, 5.0]);
+        a /= m(1, 1, &[0.5]);
+        assert_eq!(a, m(2, 3, &[0.0, 2.0, 4.0, 0.0, 2.0, 4.0]));
+
+        let mut wide = m(2, 4, &[0.0; 8]);
+        let mut right = wide.submatrix_mut(0..2, 2..4);
+        right += m(1, 2, &[1.0, 2.0]);
+        right
+            .try_hadamard_assign(&m(2, 1, &[3.0, -1.0]).view())
+            .unwrap();
+        assert_eq!(wide, m(2, 4, &[0.0, 0.0, 3.0, 6.0, 0.0, 0.0, -1.0, -2.0]));
+
+        // the left side never grows
+        let mut row = m(1, 3, &[1.0, 2.0, 3.0]);
+        assert_eq!(
+            row.try_sub_assign(&a),
+            Err(MatrixError::ShapeMismatch {
+                op: "sub_assign",
+                expected: (1, 3),
+                actual: (2, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn incompatible_shapes_give_clear_errors() {
+        let a = m(2, 3, &[0.0; 6]);
+        let b = m(3, 2, &[0.0; 6]);
+        let err = a.try_add(&b).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "add: can not broadcast a 2x3 matrix with a 3x2 matrix"
+        );
+        assert!(matches!(
+            a.try_div(&m(1, 2, &[1.0, 1.0])),
+            Err(MatrixError::Broadcast { op: "div", .. })
+        ));
+        assert!(a.try_hadamard(&m(3, 1, &[1.0; 3])).is_err());
+    }
+
+    #[test]
+    fn forward_adds_the_bias_to_every_sample() {
+        let mut model =
+            NNArch::create(&[2, 3, 2], &[Activation::Tanh, Activation::Identity]).with_seed(22);
+        model.randomize_range(-1.0..1.0);
+        let input = m(3, 2, &[0.0, 1.0, 0.5, -0.5, 1.0, 1.0]);
+        let batch = model.forward_batch(&input).clone();
+        for i in 0..3 {
+            let single = model.forward_batch(&input.row_view(i)).clone();
+            assert_eq!(single.view(), batch.row_view(i));
+        }
+    }
+}
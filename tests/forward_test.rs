@@ -0,0 +1,40 @@
+#[cfg(test)]
+pub mod forward_tests {
+    use mm_nn::nn::{Activation, NNArch, NNMatrix, T};
+
+    #[test]
+    fn forward_batch_matches_single_rows() {
+        let td: [T; 8] = [0.1, 0.9, 0.4, 0.2, 0.7, 0.3, 0.0, 1.0];
+        let input = NNMatrix::new(Some(&td[..]), 4, 2, 2);
+
+        let mut model =
+            NNArch::create(&[2, 3, 2], &[Activation::Tanh, Activation::Softmax]).with_seed(8);
+        model.randomize();
+        let batch = model.forward_batch(&input).clone();
+        assert_eq!(batch.rows, 4);
+        assert_eq!(batch.cols, 2);
+
+        let mut single = NNArch::create(&[2, 3, 2], &[Activation::Tanh, Activation::Softmax]);
+        for (dst, src) in single.params_mut().into_iter().zip(model.params()) {
+            *dst = src.clone();
+        }
+        for i in 0..input.rows {
+            single.get_input_mut().copy_row_from(&input, i);
+            single.forward();
+            assert_eq!(single.get_output().get_row(0), batch.get_row(i));
+        }
+    }
+
+    #[test]
+    fn forward_batch_adds_bias_to_every_row() {
+        let input: NNMatrix = NNMatrix::new(Some(&[1.0, 2.0, 3.0]), 3, 1, 1);
+        let mut model: NNArch = NNArch::create(&[1, 2], &[Activation::Identity]);
+        model.wl[0] = NNMatrix::new(Some(&[1.0, -1.0]), 1, 2, 2);
+        model.bl[0] = NNMatrix::new(Some(&[10.0, 20.0]), 1, 2, 2);
+
+        let output = model.forward_batch(&input);
+        assert_eq!(&output.get_row(0)[..], &[11.0, 19.0]);
+        assert_eq!(&output.get_row(1)[..], &[12.0, 18.0]);
+        assert_eq!(&output.get_row(2)[..], &[13.0, 17.0]);
+    }
+}
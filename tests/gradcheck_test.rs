@@ -0,0 +1,31 @@
+#[cfg(test)]
+pub mod gradcheck_tests {
+    use mm_nn::nn::{gradcheck, Activation, NNArch, NNMatrix, T};
+
+    const XOR: [T; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+    #[test]
+    fn gradcheck_backprop_agrees() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+
+        let mut model = NNArch::create(&[2, 4, 3, 1], &[Activation::Sigmoid; 3]).with_seed(3);
+        model.randomize();
+
+        let report = gradcheck(&mut model, &df_input, &df_output, 1e-2);
+        assert_eq!(report.layers.len(), 3);
+        assert!(report.max_abs() < 1e-3, "{report}");
+    }
+
+    #[test]
+    fn gradcheck_leaves_model_unchanged() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+
+        let mut model = NNArch::create(&[2, 2, 1], &[Activation::Sigmoid; 2]).with_seed(4);
+        model.randomize();
+        let before = model.cost(&df_input, &df_output);
+        gradcheck(&mut model, &df_input, &df_output, 1e-2);
+        assert_eq!(before, model.cost(&df_input, &df_output));
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+pub mod half_tests {
+    use mm_nn::nn::{
+        bf16, f16, Activation, Half, HalfNN, HalfReport, Initializer, Loss, MatrixError,
+        ModelFileError, NNArch, NNMatrix, Storage, T,
+    };
+
+    fn model() -> NNArch {
+        let activations = [Activation::Relu, Activation::Tanh, Activation::Softmax];
+        let mut model = NNArch::create(&[4, 16, 8, 3], &activations)
+            .with_loss(Loss::CategoricalCrossEntropy)
+            .with_seed(19);
+        model.initialize(Initializer::HeUniform, Initializer::Constant(0.01));
+        model
+    }
+
+    fn data() -> (NNMatrix, NNMatrix) {
+        let mut input: NNMatrix = NNMatrix::empty(30, 4);
+        let mut output: NNMatrix = NNMatrix::empty(30, 3);
+        for i in 0..30 {
+            let x = i as T / 30.0;
+            for j in 0..4 {
+                input[(i, j)] = (x * (j + 1) as T * 3.0).sin();
+            }
+            output[(i, i % 3)] = 1.0;
+        }
+        (input, output)
+    }
+
+    #[test]
+    fn storage_rounds_to_nearest() {
+        assert_eq!(Storage::F16.decode(Storage::F16.encode(1.0)), 1.0);
+        assert_eq!(Storage::Bf16.decode(Storage::Bf16.encode(-0.5)), -0.5);
+        assert_eq!(Storage::F16.decode(Storage::F16.encode(65504.0)), 65504.0);
+        // bf16 keeps the range of f32, f16 overflows
+        assert!(Storage::F16.decode(Storage::F16.encode(1e30)).is_infinite());
+        let big = Storage::Bf16.decode(Storage::Bf16.encode(1e30));
+        assert!((big - 1e30).abs() / 1e30 < 1e-2);
+        // f16 has the longer mantissa
+        let v = 0.1234567;
+        let f16_err = (Storage::F16.decode(Storage::F16.encode(v)) - v).abs();
+        let bf16_err = (Storage::Bf16.decode(Storage::Bf16.encode(v)) - v).abs();
+        assert!(f16_err < bf16_err);
+    }
+
+    fn check_close<H: Half>(model: &mut NNArch) -> HalfReport {
+        let (input, output) = data();
+        let mut half = model.to_half::<H>();
+        let report = half.report(model, &input, &output);
+        assert_eq!(report.storage, H::STORAGE);
+        assert_eq!(report.half_bytes * 2, report.bytes);
+        assert!(report.max_abs < 2e-2, "{report}");
+        assert!((report.half_cost - report.cost).abs() < 2e-2, "{report}");
+
+        // the inference path matches the widened model run in f32
+        let mut wide: NNArch = half.to_arch();
+        let expected = wide.forward_batch(&input).clone();
+        let actual = half.forward_batch(&input);
+        for (a, e) in actual.data_frame.iter().zip(expected.data_frame.iter()) {
+            assert!((a - e).abs() < 1e-6);
+        }
+        report
+    }
+
+    #[test]
+    fn half_model_stays_close_to_f32() {
+        let mut model = model();
+        let f16 = check_close::<f16>(&mut model);
+        let bf16 = check_close::<bf16>(&mut model);
+        assert!(f16.max_abs < bf16.max_abs);
+    }
+
+    #[test]
+    fn matrix_stores_half_values() {
+        let m: NNMatrix = NNMatrix::new(Some(&[0.1, -2.0, 65504.0, 1e-3]), 2, 2, 2);
+        let half: NNMatrix<f16> = m.cast();
+        assert_eq!(std::mem::size_of_val(&*half.data_frame), 8);
+        assert_eq!(half[(0, 1)], f16::from_f32(-2.0));
+        let wide: NNMatrix = half.cast();
+        for (w, v) in wide.iter().zip(m.iter()) {
+            assert_eq!(*w, Storage::F16.decode(Storage::F16.encode(*v)));
+        }
+    }
+
+    #[test]
+    fn set_bias_updates_the_inference_path() {
+        let mut model: NNArch = NNArch::create(&[1, 1], &[Activation::Identity]);
+        model.wl[0][(0, 0)] = 2.0;
+        let mut half = model.to_half::<bf16>();
+        let input = NNMatrix::new(Some(&[3.0]), 1, 1, 1);
+        assert_eq!(half.forward_batch(&input)[(0, 0)], 6.0);
+        half.set_bias(0, NNMatrix::new(Some(&[bf16::from_f32(0.5)]), 1, 1, 1));
+        assert_eq!(half.bl()[0][(0, 0)], bf16::from_f32(0.5));
+        assert_eq!(half.forward_batch(&input)[(0, 0)], 6.5);
+    }
+
+    #[test]
+    fn half_model_file_round_trips() {
+        let model = model();
+        let half = model.to_half::<bf16>();
+        let bytes = half.to_bytes();
+        assert!(bytes.len() < model.to_bytes().len() * 6 / 10);
+
+        let loaded = HalfNN::<bf16>::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.storage(), Storage::Bf16);
+        assert_eq!(loaded.arch(), model.arch());
+        assert_eq!(loaded.activations, model.activations);
+        assert_eq!(loaded.loss, model.loss);
+        assert_eq!(loaded.wl, half.wl);
+        assert_eq!(loaded.bl(), half.bl());
+
+        // the full precision loader widens the values, a bf16 model writes the same file
+        let wide = NNArch::<f32>::from_bytes(&bytes).unwrap();
+        assert_eq!(wide.wl, half.to_arch::<f32>().wl);
+        assert_eq!(half.to_arch::<bf16>().to_bytes(), bytes);
+
+        assert!(matches!(
+            HalfNN::<bf16>::from_bytes(&model.to_bytes()),
+            Err(ModelFileError::UnsupportedElementType(1))
+        ));
+        assert!(matches!(
+            HalfNN::<f16>::from_bytes(&bytes),
+            Err(ModelFileError::UnsupportedElementType(4))
+        ));
+    }
+
+    #[test]
+    fn nan_and_bounds_match_the_f32_path() {
+        let mut model: NNArch = NNArch::create(&[2, 1], &[Activation::Identity]);
+        model.wl[0] = NNMatrix::new(Some(&[T::INFINITY, 1.0]), 2, 1, 1);
+        let mut half = model.to_half::<f16>();
+        // 0 * inf is nan in f32, the half model has to agree instead of skipping the zero
+        let input = NNMatrix::new(Some(&[0.0, 1.0]), 1, 2, 2);
+        assert!(model.forward_batch(&input)[(0, 0)].is_nan());
+        assert!(half.forward_batch(&input)[(0, 0)].is_nan());
+
+        assert_eq!(half.wl[0].get_at(1, 0), f16::ONE);
+        assert_eq!(
+            half.wl[0].try_get(2, 0),
+            Err(MatrixError::OutOfBounds {
+                row: 2,
+                col: 0,
+                rows: 2,
+                cols: 1
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index (0, 1) is out of bounds of a 2x1 matrix")]
+    fn half_get_at_checks_bounds() {
+        let model: NNArch = NNArch::create(&[2, 1], &[Activation::Identity]);
+        model.to_half::<bf16>().wl[0].get_at(0, 1);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+pub mod init_tests {
+    use mm_nn::nn::{Activation, Initializer, NNArch, NNMatrix, T};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn xavier_uniform_stays_in_limit() {
+        let mut model: NNArch =
+            NNArch::create(&[30, 10, 2], &[Activation::Tanh, Activation::Sigmoid]).with_seed(12);
+        model.initialize(Initializer::XavierUniform, Initializer::Constant(0.5));
+
+        let limit = (6.0 as T / 40.0).sqrt();
+        for row in 0..30 {
+            for col in 0..10 {
+                assert!(model.wl[0].get_at(row, col).abs() <= limit);
+            }
+        }
+        assert!(model.bl[0].get_row(0).iter().all(|&b| b == 0.5));
+        assert!(model.bl[1].get_row(0).iter().all(|&b| b == 0.5));
+    }
+
+    #[test]
+    fn constant_keeps_f64_precision() {
+        let mut m: NNMatrix<f64> = NNMatrix::empty(2, 3);
+        Initializer::Constant(0.1).init(&mut m, 2, 3);
+        assert!(m.iter().all(|&v| v == 0.1));
+    }
+
+    #[test]
+    fn he_normal_has_expected_spread() {
+        let mut m = NNMatrix::empty(200, 100);
+        Initializer::HeNormal.init_with(&mut m, 200, 100, &mut StdRng::seed_from_u64(13));
+        let n = (m.rows * m.cols) as T;
+        let mean: T = m.data_frame.iter().sum::<T>() / n;
+        let var: T = m
+            .data_frame
+            .iter()
+            .map(|v| (v - mean) * (v - mean))
+            .sum::<T>()
+            / n;
+        assert!(mean.abs() < 0.01);
+        assert!((var - 2.0 / 200.0).abs() < 0.001, "{var}");
+    }
+
+    #[test]
+    fn orthogonal_columns_are_orthonormal() {
+        for (rows, cols) in [(8, 3), (3, 8), (5, 5)] {
+            let mut m: NNMatrix = NNMatrix::empty(rows, cols);
+            Initializer::Orthogonal.init_with(&mut m, rows, cols, &mut StdRng::seed_from_u64(14));
+            let by_cols = rows >= cols;
+            let (count, len) = if by_cols { (cols, rows) } else { (rows, cols) };
+            for u in 0..count {
+                for v in 0..count {
+                    let mut dot: T = 0.0;
+                    for k in 0..len {
+                        dot += if by_cols {
+                            m.get_at(k, u) * m.get_at(k, v)
+                        } else {
+                            m.get_at(u, k) * m.get_at(v, k)
+                        };
+                    }
+                    let expected = if u == v { 1.0 } else { 0.0 };
+                    assert!(
+                        (dot - expected).abs() < 1e-4,
+                        "{rows}x{cols} {u},{v}: {dot}"
+                    );
+                }
+            }
+        }
+    }
+}
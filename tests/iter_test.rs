@@ -0,0 +1,85 @@
+#[cfg(test)]
+pub mod iter_tests {
+    use mm_nn::nn::{NNMatrix, T};
+
+    // 2 x 2 values in a buffer with stride 3, the third column is padding
+    fn strided() -> NNMatrix {
+        NNMatrix::new(Some(&[1.0, 2.0, 9.0, 3.0, 4.0, 9.0]), 2, 2, 3)
+    }
+
+    #[test]
+    fn iterators_skip_the_stride_padding() {
+        let m = strided();
+        assert_eq!(m.row(1), &[3.0, 4.0]);
+        assert_eq!(m.rows().len(), 2);
+        assert_eq!(m.rows().next_back(), Some(&[3.0, 4.0][..]));
+        assert_eq!(m.iter().copied().collect::<Vec<T>>(), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(m.col_iter(1).copied().collect::<Vec<T>>(), [2.0, 4.0]);
+
+        let wide = NNMatrix::new(Some(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), 2, 3, 3);
+        let right = wide.cols_view(1..3);
+        assert_eq!(right.iter().copied().sum::<T>(), 16.0);
+        assert_eq!(right.col_iter(0).copied().collect::<Vec<T>>(), [2.0, 5.0]);
+    }
+
+    #[test]
+    fn mutable_iterators() {
+        let mut m = strided();
+        for v in m.iter_mut() {
+            *v *= 10.0;
+        }
+        m.row_mut(0)[1] = 0.0;
+        for row in m.rows_mut() {
+            row[0] += 1.0;
+        }
+        assert_eq!(
+            m.iter().copied().collect::<Vec<T>>(),
+            [11.0, 0.0, 31.0, 40.0]
+        );
+        // the padding is never handed out
+        assert_eq!(m.data_frame[2], 9.0);
+    }
+
+    #[test]
+    fn enumerate_indexed_matches_get_at() {
+        let m = strided();
+        let indexed: Vec<_> = m.enumerate_indexed().collect();
+        assert_eq!(indexed.len(), 4);
+        for ((i, j), &v) in indexed {
+            assert_eq!(v, m.get_at(i, j));
+        }
+    }
+
+    #[test]
+    fn collect_into_a_matrix() {
+        let rows: NNMatrix = (0..3).map(|i| vec![i as T, i as T * 2.0]).collect();
+        assert_eq!(
+            rows,
+            NNMatrix::new(Some(&[0.0, 0.0, 1.0, 2.0, 2.0, 4.0]), 3, 2, 2)
+        );
+        assert_eq!(
+            NNMatrix::from(vec![vec![0.0, 0.0], vec![1.0, 2.0], vec![2.0, 4.0]]),
+            rows
+        );
+
+        let row: NNMatrix = rows.col_iter(1).copied().collect();
+        assert_eq!((row.rows, row.cols), (1, 3));
+        assert_eq!(row.row(0), &[0.0, 2.0, 4.0]);
+        assert_eq!(rows.iter().copied().collect::<NNMatrix>().cols, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1 has 1 values, expected 2")]
+    fn ragged_rows_panic() {
+        let _ = NNMatrix::from(vec![vec![1.0, 2.0], vec![3.0]]);
+    }
+
+    #[test]
+    fn zero_width_rows() {
+        let mut m: NNMatrix = NNMatrix::empty(2, 0);
+        assert!(m.row_mut(1).is_empty());
+        assert_eq!(m.rows_mut().count(), 2);
+        assert_eq!(m.sum_rows(), NNMatrix::empty(1, 0));
+        assert_eq!(NNMatrix::<T>::from(vec![vec![], vec![]]), m);
+    }
+}
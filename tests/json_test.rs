@@ -0,0 +1,100 @@
+#[cfg(test)]
+pub mod json_tests {
+    use mm_nn::nn::{Activation, Initializer, Loss, ModelFileError, NNArch, NNMatrix, T};
+
+    #[test]
+    fn json_round_trips_exactly() {
+        let mut model: NNArch =
+            NNArch::create(&[4, 3, 2], &[Activation::Elu(0.7), Activation::Sigmoid])
+                .with_loss(Loss::Huber(0.3))
+                .with_seed(8);
+        model.initialize(Initializer::XavierNormal, Initializer::LecunUniform);
+
+        let loaded = NNArch::from_json(&model.to_json()).unwrap();
+        assert_eq!(loaded.activations, model.activations);
+        assert_eq!(loaded.loss, model.loss);
+        assert_eq!(loaded.wl, model.wl);
+        assert_eq!(loaded.bl, model.bl);
+    }
+
+    #[test]
+    fn hand_written_xor_model() {
+        // or and nand in the hidden layer, and in the output layer
+        let json = r#"{
+            "version": 1,
+            "layers": [2, 2, 1],
+            "activations": ["Sigmoid", "Sigmoid"],
+            "loss": "Mse",
+            "weights": [
+                { "rows": 2, "cols": 2, "data": [[20.0, -20.0], [20.0, -20.0]] },
+                { "rows": 2, "cols": 1, "data": [[20.0], [20.0]] }
+            ],
+            "biases": [
+                { "rows": 1, "cols": 2, "data": [[-10.0, 30.0]] },
+                { "rows": 1, "cols": 1, "data": [[-30.0]] }
+            ]
+        }"#;
+        let mut model = NNArch::from_json(json).unwrap();
+        let td: [T; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+        let df_input = NNMatrix::new(Some(&td[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&td[2..]), 4, 1, 3);
+        assert!(model.cost(&df_input, &df_output) < 1e-6);
+    }
+
+    #[test]
+    fn rejects_wrong_shapes() {
+        let model: NNArch = NNArch::create(&[2, 2, 1], &[Activation::Sigmoid; 2]);
+        let json = model.to_json().replacen("\"rows\": 2", "\"rows\": 3", 1);
+        assert!(matches!(
+            NNArch::<f32>::from_json(&json),
+            Err(ModelFileError::Json(_))
+        ));
+
+        let json = model.to_json().replacen(
+            "\"layers\": [\n    2,\n    2,",
+            "\"layers\": [\n    2,\n    3,",
+            1,
+        );
+        assert!(matches!(
+            NNArch::<f32>::from_json(&json),
+            Err(ModelFileError::ShapeMismatch { layer: 0, .. })
+        ));
+
+        let json = model.to_json().replacen("\"Sigmoid\",", "", 1);
+        assert!(matches!(
+            NNArch::<f32>::from_json(&json),
+            Err(ModelFileError::LayerCount {
+                field: "activations",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_huge_layers_and_non_finite_values() {
+        let json = r#"{"version": 1, "layers": [200000, 200000], "activations": ["Sigmoid"],
+            "loss": "Mse", "weights": [{"rows": 0, "cols": 0, "data": []}],
+            "biases": [{"rows": 1, "cols": 1, "data": [[0.0]]}]}"#;
+        assert!(matches!(
+            NNArch::<f32>::from_json(json),
+            Err(ModelFileError::ShapeMismatch { layer: 0, .. })
+        ));
+
+        // a zero width hidden layer loads
+        let mut model: NNArch = NNArch::create(&[2, 0, 1], &[Activation::Relu; 2]);
+        let loaded = NNArch::<f32>::from_json(&model.to_json()).unwrap();
+        assert_eq!(loaded.arch(), vec![2, 0, 1]);
+
+        model.bl[1][(0, 0)] = T::NAN;
+        let path = std::env::temp_dir().join(format!("mm-nn-nan-{}.json", std::process::id()));
+        assert!(matches!(
+            model.save_json(&path),
+            Err(ModelFileError::NonFinite { layer: 1 })
+        ));
+        assert!(!path.exists());
+        assert!(matches!(
+            NNArch::<f32>::from_json(&model.to_json()),
+            Err(ModelFileError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+pub mod layers_tests {
+    use mm_nn::nn::layers::{Dense, Dropout};
+    use mm_nn::nn::{Activation, Initializer, Layer, Loss, NNArch, NNMatrix, Sequential, Sgd, T};
+    use std::fmt;
+
+    fn m(rows: usize, cols: usize, values: &[T]) -> NNMatrix {
+        NNMatrix::new(Some(values), rows, cols, cols)
+    }
+
+    fn assert_close(a: &NNMatrix, b: &NNMatrix) {
+        assert_eq!((a.rows, a.cols), (b.rows, b.cols));
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-5, "{a}\n{b}");
+        }
+    }
+
+    fn data() -> (NNMatrix, NNMatrix) {
+        let input = m(4, 2, &[0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        let output = m(4, 2, &[1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        (input, output)
+    }
+
+    fn model(activations: &[Activation], loss: Loss) -> NNArch {
+        let mut model = NNArch::create(&[2, 4, 2], activations)
+            .with_loss(loss)
+            .with_seed(9);
+        model.initialize(Initializer::XavierUniform, Initializer::Constant(0.1));
+        model
+    }
+
+    #[test]
+    fn sequential_matches_nnarch() {
+        let (input, output) = data();
+        for (activations, loss) in [
+            ([Activation::Tanh, Activation::Sigmoid], Loss::Mse),
+            (
+                [Activation::Relu, Activation::Softmax],
+                Loss::CategoricalCrossEntropy,
+            ),
+        ] {
+            let mut model = model(&activations, loss);
+            let mut seq = Sequential::from(&model);
+            assert_eq!(seq.layers.len(), 4);
+            assert_close(&seq.forward(&input), model.forward_batch(&input));
+
+            let mut gradient = NNArch::create(&[2, 4, 2], &activations);
+            model.backprop(&mut gradient, &input, &output);
+            let cost = seq.backprop(&input, &output);
+            assert!((cost - model.cost(&input, &output)).abs() < 1e-6);
+            let grads = seq.grads();
+            for i in 0..2 {
+                assert_close(grads[2 * i], &gradient.wl[i]);
+                assert_close(grads[2 * i + 1], &gradient.bl[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn step_matches_learn() {
+        let (input, output) = data();
+        let activations = [Activation::Sigmoid, Activation::Sigmoid];
+        let mut model = model(&activations, Loss::Mse);
+        let mut gradient = NNArch::create(&[2, 4, 2], &activations);
+        let mut seq = Sequential::from(&model);
+
+        for _ in 0..5 {
+            model.backprop(&mut gradient, &input, &output);
+            model.learn(&gradient, 0.5);
+            seq.backprop(&input, &output);
+            seq.step(&mut Sgd::new(0.5));
+        }
+        let params = seq.params();
+        assert_close(params[0], &model.wl[0]);
+        assert_close(params[3], &model.bl[1]);
+    }
+
+    #[test]
+    fn dropout_only_drops_while_training() {
+        let input = m(20, 50, &[1.0; 1000]);
+        let mut dropout = Dropout::new(0.25, 3);
+        let out = dropout.forward(&input);
+        let dropped = out.iter().filter(|&&v| v == 0.0).count();
+        assert!((200..300).contains(&dropped), "{dropped}");
+        assert!(out
+            .iter()
+            .all(|&v| v == 0.0 || (v - 1.0 / 0.75).abs() < 1e-6));
+        // the gradient flows through the same values
+        assert_eq!(dropout.backward(&input), out);
+
+        dropout.set_training(false);
+        assert_eq!(dropout.forward(&input), input);
+        assert_eq!(Dropout::new(0.25, 3).forward(&input), out);
+        assert_ne!(Dropout::new(0.25, 4).forward(&input), out);
+    }
+
+    /// y = x * scale with a trainable scale, to check a new kind of layer needs nothing but the
+    /// trait.
+    #[derive(Debug)]
+    struct Scale {
+        scale: NNMatrix,
+        grad: NNMatrix,
+        input: NNMatrix,
+    }
+
+    impl Layer for Scale {
+        fn forward(&mut self, input: &NNMatrix) -> NNMatrix {
+            self.input = input.clone();
+            input * self.scale[(0, 0)]
+        }
+
+        fn backward(&mut self, grad: &NNMatrix) -> NNMatrix {
+            self.grad[(0, 0)] += grad.hadamard(&self.input).sum();
+            grad * self.scale[(0, 0)]
+        }
+
+        fn params_and_grads(&mut self) -> Vec<(&mut NNMatrix, &NNMatrix)> {
+            vec![(&mut self.scale, &self.grad)]
+        }
+
+        fn zero_grads(&mut self) {
+            self.grad.fill(0.0);
+        }
+    }
+
+    impl fmt::Display for Scale {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "Scale {}", self.scale[(0, 0)])
+        }
+    }
+
+    #[test]
+    fn custom_layers_train() {
+        let input = m(4, 1, &[1.0, 2.0, 3.0, 4.0]);
+        let output = m(4, 1, &[3.0, 6.0, 9.0, 12.0]);
+        let mut seq = Sequential::new()
+            .with(Scale {
+                scale: m(1, 1, &[1.0]),
+                grad: NNMatrix::empty(1, 1),
+                input: NNMatrix::empty(0, 1),
+            })
+            .with(Dense::from_params(m(1, 1, &[1.0]), NNMatrix::empty(1, 1)));
+        let first = seq.cost(&input, &output);
+        let mut sgd = Sgd::new(0.01);
+        for _ in 0..200 {
+            seq.backprop(&input, &output);
+            seq.step(&mut sgd);
+        }
+        assert!(seq.cost(&input, &output) < first * 1e-3);
+        assert!(seq.to_string().contains("Scale"));
+        assert!(seq.to_string().contains("Dense 1 -> 1"));
+    }
+
+    #[test]
+    fn nnarch_runs_on_its_layers() {
+        let (input, output) = data();
+        let mut model = model(&[Activation::Tanh, Activation::Sigmoid], Loss::Mse);
+        let mut gradient = NNArch::create(&[2, 4, 2], &[Activation::Tanh, Activation::Sigmoid]);
+        let weights = model.wl.clone();
+        let first = model.cost(&input, &output);
+        model.backprop(&mut gradient, &input, &output);
+        assert_eq!(model.wl, weights);
+        model.learn(&gradient, 0.5);
+        assert!(model.cost(&input, &output) < first);
+
+        let text = model.to_string();
+        assert!(text.contains("0: Dense 2 -> 4"));
+        assert!(text.contains("1: Activation Tanh"));
+        assert!(text.contains("3: Activation Sigmoid"));
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+pub mod loss_tests {
+    use mm_nn::nn::{gradcheck, Activation, Loss, NNArch, NNMatrix, T};
+
+    const LOSSES: [Loss; 5] = [
+        Loss::Mse,
+        Loss::Mae,
+        Loss::Huber(0.25),
+        Loss::BinaryCrossEntropy,
+        Loss::CategoricalCrossEntropy,
+    ];
+
+    #[test]
+    fn gradient_matches_slope() {
+        let eps: T = 1e-3;
+        let predicted: [T; 3] = [0.2, 0.7, 0.4];
+        let expected: [T; 3] = [0.0, 1.0, 0.9];
+        for loss in LOSSES {
+            let mut grad = [0.0; 3];
+            loss.gradient(&predicted, &expected, &mut grad);
+            for j in 0..3 {
+                let mut plus = predicted;
+                let mut minus = predicted;
+                plus[j] += eps;
+                minus[j] -= eps;
+                let numeric =
+                    (loss.value(&plus, &expected) - loss.value(&minus, &expected)) / (2.0 * eps);
+                assert!(
+                    (numeric - grad[j]).abs() < 1e-2,
+                    "{loss:?} col {j}: {numeric} vs {}",
+                    grad[j]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fused_value_matches_plain_value() {
+        let z: [T; 3] = [0.5, -1.0, 2.0];
+        let expected: [T; 3] = [0.0, 0.0, 1.0];
+        let zm = NNMatrix::new(Some(&z), 1, 3, 3);
+
+        let mut a = NNMatrix::empty(1, 3);
+        Activation::Softmax.forward(&zm, &mut a);
+        let plain = Loss::CategoricalCrossEntropy.value(&a.get_row(0), &expected);
+        let fused = Loss::CategoricalCrossEntropy.fused_value(&z, &expected);
+        assert!((plain - fused).abs() < 1e-5);
+
+        Activation::Sigmoid.forward(&zm, &mut a);
+        let plain = Loss::BinaryCrossEntropy.value(&a.get_row(0), &expected);
+        let fused = Loss::BinaryCrossEntropy.fused_value(&z, &expected);
+        assert!((plain - fused).abs() < 1e-5);
+    }
+
+    #[test]
+    fn fused_value_is_stable_for_large_logits() {
+        let loss: T = Loss::CategoricalCrossEntropy.fused_value(&[1000.0, -1000.0], &[0.0, 1.0]);
+        assert!(loss.is_finite());
+        assert!((loss - 2000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn gradcheck_softmax_cross_entropy() {
+        let td: [T; 15] = [
+            0.1, 0.9, 1.0, 0.0, 0.0, 0.8, 0.3, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 0.0, 1.0,
+        ];
+        let df_input = NNMatrix::new(Some(&td[..]), 3, 2, 5);
+        let df_output = NNMatrix::new(Some(&td[2..]), 3, 3, 5);
+
+        let mut model = NNArch::create(&[2, 4, 3], &[Activation::Tanh, Activation::Softmax])
+            .with_loss(Loss::CategoricalCrossEntropy)
+            .with_seed(6);
+        model.randomize();
+        let report = gradcheck(&mut model, &df_input, &df_output, 1e-2);
+        assert!(report.max_abs() < 1e-3, "{report}");
+    }
+
+    #[test]
+    fn cost_with_overrides_model_loss() {
+        let td: [T; 4] = [0.0, 1.0, 1.0, 0.0];
+        let df_input = NNMatrix::new(Some(&td[..]), 2, 1, 2);
+        let df_output = NNMatrix::new(Some(&td[1..]), 2, 1, 2);
+
+        let mut model = NNArch::create(&[1, 1], &[Activation::Identity]);
+        let mse = model.cost(&df_input, &df_output);
+        let mae = model.cost_with(Loss::Mae, &df_input, &df_output);
+        assert_eq!(mse, 0.5);
+        assert_eq!(mae, 0.5);
+        model = model.with_loss(Loss::Huber(0.5));
+        assert_eq!(model.cost(&df_input, &df_output), 0.1875);
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+pub mod matmul_tests {
+    use mm_nn::nn::{matmul_into, try_matmul_into, MatrixError, NNMatrix};
+
+    /// rows x cols matrix with small values that differ in every element.
+    fn filled(rows: usize, cols: usize, seed: usize) -> NNMatrix {
+        let mut m = NNMatrix::empty(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                m[(i, j)] = ((i * 31 + j * 17 + seed) % 13) as f32 / 13.0 - 0.5;
+            }
+        }
+        m
+    }
+
+    fn reference(a: &NNMatrix, b: &NNMatrix) -> NNMatrix {
+        let mut c = NNMatrix::empty(a.rows, b.cols);
+        for i in 0..a.rows {
+            for j in 0..b.cols {
+                for k in 0..a.cols {
+                    c[(i, j)] += a[(i, k)] * b[(k, j)];
+                }
+            }
+        }
+        c
+    }
+
+    fn assert_close(x: &NNMatrix, y: &NNMatrix) {
+        assert_eq!((x.rows, x.cols), (y.rows, y.cols));
+        for i in 0..x.rows {
+            for j in 0..x.cols {
+                assert!((x[(i, j)] - y[(i, j)]).abs() < 1e-4, "at ({i}, {j})");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_reference_across_tile_edges() {
+        // sizes around the 4x8 microkernel, the 128 deep and 256 wide blocks
+        for (rows, inner, cols) in [(1, 784, 16), (7, 130, 19), (9, 3, 260), (4, 128, 8)] {
+            let a = filled(rows, inner, 1);
+            let b = filled(inner, cols, 2);
+            let mut out = filled(rows, cols, 3);
+            matmul_into(&a, &b, &mut out.view_mut());
+            assert_close(&out, &reference(&a, &b));
+            assert_close(&(&a * &b), &out);
+        }
+    }
+
+    #[test]
+    fn strided_views_in_and_out() {
+        let a = filled(6, 10, 4);
+        let b = filled(10, 12, 5);
+        let mut out = NNMatrix::empty(8, 12);
+        let a_view = a.submatrix(1..6, 0..9);
+        let b_view = b.submatrix(1..10, 2..12);
+        matmul_into(&a_view, &b_view, &mut out.submatrix_mut(2..7, 1..11));
+
+        let expected = reference(&a_view.to_matrix(), &b_view.to_matrix());
+        assert_close(&out.submatrix(2..7, 1..11).to_matrix(), &expected);
+        // nothing outside the window is written
+        assert!(out.get_row(0).iter().all(|&v| v == 0.0));
+        assert!((0..8).all(|i| out[(i, 0)] == 0.0 && out[(i, 11)] == 0.0));
+    }
+
+    #[test]
+    fn reports_wrong_shapes() {
+        let a: NNMatrix = NNMatrix::empty(2, 3);
+        let mut out = NNMatrix::empty(2, 4);
+        assert_eq!(
+            try_matmul_into(&a, &NNMatrix::empty(4, 4), &mut out.view_mut()),
+            Err(MatrixError::ShapeMismatch {
+                op: "matmul",
+                expected: (3, 4),
+                actual: (4, 4),
+            })
+        );
+        assert_eq!(
+            try_matmul_into(&a, &NNMatrix::empty(3, 5), &mut out.view_mut()),
+            Err(MatrixError::ShapeMismatch {
+                op: "matmul_into",
+                expected: (2, 5),
+                actual: (2, 4),
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn view_larger_than_its_buffer_panics() {
+        let a = filled(4, 4, 1);
+        let b = filled(4, 4, 2);
+        let mut view = a.view();
+        view.rows = 64;
+        let mut out = NNMatrix::empty(64, 4);
+        matmul_into(&view, &b, &mut out.view_mut());
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+pub mod matrix_error_tests {
+    use mm_nn::nn::{Activation, MatrixError, NNArch, NNMatrix};
+
+    #[test]
+    fn try_matmul_reports_shapes() {
+        let a: NNMatrix = NNMatrix::empty(2, 3);
+        let b = NNMatrix::empty(4, 5);
+        assert_eq!(
+            a.try_matmul(&b),
+            Err(MatrixError::ShapeMismatch {
+                op: "matmul",
+                expected: (3, 5),
+                actual: (4, 5),
+            })
+        );
+        let c = a.try_matmul(&NNMatrix::empty(3, 5)).unwrap();
+        assert_eq!((c.rows, c.cols), (2, 5));
+    }
+
+    #[test]
+    fn try_add_and_add_assign() {
+        let a: NNMatrix = NNMatrix::new(Some(&[1.0, 2.0]), 1, 2, 2);
+        let mut b = NNMatrix::new(Some(&[3.0, 4.0]), 1, 2, 2);
+        assert_eq!(a.try_add(&b).unwrap().get_row(0)[..], [4.0, 6.0]);
+        assert!(b.try_add_assign(&a).is_ok());
+        assert_eq!(b.get_row(0)[..], [4.0, 6.0]);
+
+        let err = b.try_add_assign(&NNMatrix::empty(2, 2)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "add_assign: expected a 1x2 matrix found 2x2"
+        );
+    }
+
+    #[test]
+    fn try_get_checks_bounds() {
+        let mut m: NNMatrix = NNMatrix::empty(2, 3);
+        assert!(m.try_set(1, 2, 5.0).is_ok());
+        assert_eq!(m.try_get(1, 2), Ok(5.0));
+        assert_eq!(
+            m.try_get(2, 0),
+            Err(MatrixError::OutOfBounds {
+                row: 2,
+                col: 0,
+                rows: 2,
+                cols: 3,
+            })
+        );
+        assert!(m.try_get(0, 3).is_err());
+        assert!(m.try_copy_row_to(&mut NNMatrix::empty(1, 2), 0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "matmul: expected a 3x1 matrix found 2x1")]
+    fn mul_panics_with_error_message() {
+        let _ = &NNMatrix::<f32>::empty(1, 3) * &NNMatrix::empty(2, 1);
+    }
+
+    #[test]
+    fn nnarch_propagates_errors() {
+        let mut model: NNArch = NNArch::create(&[2, 3, 1], &[Activation::Sigmoid; 2]);
+        let mut gradient = NNArch::create(&[2, 4, 1], &[Activation::Sigmoid; 2]);
+        let good_input = NNMatrix::empty(4, 2);
+        let good_output = NNMatrix::empty(4, 1);
+
+        assert!(model.try_forward_batch(&NNMatrix::empty(4, 3)).is_err());
+        assert!(model.try_cost(&good_input, &NNMatrix::empty(3, 1)).is_err());
+        assert_eq!(
+            model.try_backprop(&mut gradient, &good_input, &good_output),
+            Err(MatrixError::ShapeMismatch {
+                op: "backprop",
+                expected: (2, 3),
+                actual: (2, 4),
+            })
+        );
+        assert!(model.try_cost(&good_input, &good_output).is_ok());
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+pub mod model_file_tests {
+    use mm_nn::nn::{f16, Activation, HalfNN, Initializer, Loss, ModelFileError, NNArch};
+    use std::env;
+
+    fn model() -> NNArch {
+        let mut model = NNArch::create(
+            &[3, 5, 2],
+            &[Activation::LeakyRelu(0.05), Activation::Softmax],
+        )
+        .with_loss(Loss::CategoricalCrossEntropy)
+        .with_seed(4);
+        model.initialize(Initializer::HeUniform, Initializer::Constant(0.1));
+        model
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let model = model();
+        let path = env::temp_dir().join(format!("mm-nn-{}.mmnn", std::process::id()));
+        model.save(&path).unwrap();
+        let loaded = NNArch::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.arch(), vec![3, 5, 2]);
+        assert_eq!(loaded.activations, model.activations);
+        assert_eq!(loaded.loss, model.loss);
+        assert_eq!(loaded.wl, model.wl);
+        assert_eq!(loaded.bl, model.bl);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = model().to_bytes();
+        bytes[0] = b'X';
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes),
+            Err(ModelFileError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupted_values() {
+        let mut bytes = model().to_bytes();
+        let last_value = bytes.len() - 5;
+        bytes[last_value] ^= 0xff;
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes),
+            Err(ModelFileError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut bytes = model().to_bytes();
+        bytes[4] = 9;
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes),
+            Err(ModelFileError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes[..6]),
+            Err(ModelFileError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_io_error() {
+        let err =
+            NNArch::<f32>::load(env::temp_dir().join("mm-nn-does-not-exist.mmnn")).unwrap_err();
+        assert!(matches!(err, ModelFileError::Io(_)));
+        assert!(err.to_string().starts_with("io error"));
+    }
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc: u32 = 0xffff_ffff;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    /// the header of a 2 -> 1 model with the layer count and sizes replaced, checksummed but
+    /// without any layers.
+    fn header(layer_count: u32, sizes: [u32; 2]) -> Vec<u8> {
+        let bytes = NNArch::<f32>::create(&[2, 1], &[Activation::Sigmoid]).to_bytes();
+        // magic, version, element type, layer count, 2 sizes, 1 activation and the loss with
+        // their f64 parameters
+        let mut out = bytes[..37].to_vec();
+        out[7..11].copy_from_slice(&layer_count.to_le_bytes());
+        out[11..15].copy_from_slice(&sizes[0].to_le_bytes());
+        out[15..19].copy_from_slice(&sizes[1].to_le_bytes());
+        let checksum = crc32(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn huge_header_sizes_are_truncated_not_allocated() {
+        let bytes = header(1, [200_000, 200_000]);
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes),
+            Err(ModelFileError::Truncated)
+        ));
+        assert!(matches!(
+            HalfNN::<f16>::from_bytes(&bytes),
+            Err(ModelFileError::Truncated)
+        ));
+        // rows * cols overflows usize
+        let bytes = header(1, [u32::MAX, u32::MAX]);
+        assert!(matches!(
+            NNArch::<f64>::from_bytes(&bytes),
+            Err(ModelFileError::Truncated)
+        ));
+        let bytes = header(u32::MAX, [2, 1]);
+        assert!(matches!(
+            NNArch::<f32>::from_bytes(&bytes),
+            Err(ModelFileError::Truncated)
+        ));
+    }
+}
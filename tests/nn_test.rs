@@ -1,4 +1,6 @@
 #[cfg(test)]
+// the expected values keep the digits of the baseline tests
+#[allow(clippy::excessive_precision)]
 pub mod nn_tests {
     use mm_nn::nn::sigmoid;
 
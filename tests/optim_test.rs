@@ -0,0 +1,91 @@
+#[cfg(test)]
+pub mod optim_tests {
+    use mm_nn::nn::{
+        Activation, Adagrad, Adam, AdamW, Momentum, NNArch, NNMatrix, Optimizer, RmsProp, Sgd, T,
+    };
+
+    const XOR: [T; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+    fn train(optimizer: &mut dyn Optimizer) -> (T, T) {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+        let activations = [Activation::Sigmoid; 2];
+
+        let mut model = NNArch::create(&[2, 3, 1], &activations).with_seed(9);
+        model.randomize();
+        let mut gradient = NNArch::create(&[2, 3, 1], &activations);
+
+        let initial = model.cost(&df_input, &df_output);
+        for _ in 0..200 {
+            model.backprop(&mut gradient, &df_input, &df_output);
+            optimizer.step(&mut model, &gradient);
+        }
+        (initial, model.cost(&df_input, &df_output))
+    }
+
+    #[test]
+    fn every_optimizer_reduces_cost() {
+        let mut optimizers: Vec<Box<dyn Optimizer>> = vec![
+            Box::new(Sgd::new(1.0)),
+            Box::new(Momentum::new(0.5, 0.9)),
+            Box::new(Momentum::nesterov(0.5, 0.9)),
+            Box::new(Adagrad::new(0.1)),
+            Box::new(RmsProp::new(0.01)),
+            Box::new(Adam::new(0.05)),
+            Box::new(AdamW::new(0.05, 1e-4)),
+        ];
+        for optimizer in optimizers.iter_mut() {
+            let (initial, last) = train(optimizer.as_mut());
+            assert!(last < initial, "{initial} -> {last}");
+        }
+    }
+
+    #[test]
+    fn sgd_step_matches_learn() {
+        let activations = [Activation::Sigmoid; 2];
+        let mut a: NNArch = NNArch::create(&[2, 2, 1], &activations).with_seed(10);
+        a.randomize();
+        let mut b = NNArch::create(&[2, 2, 1], &activations);
+        let mut gradient = NNArch::create(&[2, 2, 1], &activations).with_seed(11);
+        gradient.randomize();
+        for (dst, src) in b.params_mut().into_iter().zip(a.params()) {
+            *dst = src.clone();
+        }
+
+        a.learn(&gradient, 0.3);
+        Sgd::new(0.3).step(&mut b, &gradient);
+        assert_eq!(a.wl, b.wl);
+        assert_eq!(a.bl, b.bl);
+    }
+
+    #[test]
+    fn adam_first_step_is_rate_sized() {
+        let mut p: NNMatrix = NNMatrix::new(Some(&[1.0, -1.0]), 1, 2, 2);
+        let g = NNMatrix::new(Some(&[5.0, -0.01]), 1, 2, 2);
+        let mut adam = Adam::new(0.1);
+        adam.update(&mut [&mut p], &[&g]);
+        // bias correction makes the first step rate * sign(g)
+        assert!((p.get_at(0, 0) - 0.9).abs() < 1e-5);
+        assert!((p.get_at(0, 1) + 0.9).abs() < 1e-3);
+        assert_eq!(adam.t, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "2 parameters but 1 gradients")]
+    fn update_needs_a_gradient_per_parameter() {
+        let (mut a, mut b): (NNMatrix, NNMatrix) = (NNMatrix::empty(1, 2), NNMatrix::empty(1, 2));
+        let g = NNMatrix::empty(1, 2);
+        Sgd::new(0.1).update(&mut [&mut a, &mut b], &[&g]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn shape_larger_than_the_buffer_panics() {
+        let mut p: NNMatrix = NNMatrix::empty(1, 1);
+        let mut g: NNMatrix = NNMatrix::empty(1, 1);
+        for m in [&mut p, &mut g] {
+            (m.rows, m.cols, m.stride) = (4096, 4096, 4096);
+        }
+        Sgd::new(1.0).update(&mut [&mut p], &[&g]);
+    }
+}
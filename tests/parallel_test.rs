@@ -0,0 +1,125 @@
+#[cfg(test)]
+pub mod parallel_tests {
+    use mm_nn::nn::{Activation, NNArch, NNMatrix};
+
+    // these hold with and without the parallel feature, with it the large batches and
+    // products are split over threads while the small ones stay on the serial path.
+
+    fn data(rows: usize) -> (NNMatrix, NNMatrix) {
+        let mut input = NNMatrix::empty(rows, 3);
+        let mut output = NNMatrix::empty(rows, 2);
+        for i in 0..rows {
+            let x = i as f32 / rows as f32;
+            input[(i, 0)] = x;
+            input[(i, 1)] = (x * 7.0).sin();
+            input[(i, 2)] = 1.0 - x * x;
+            output[(i, 0)] = if x > 0.5 { 1.0 } else { 0.0 };
+            output[(i, 1)] = x * 0.5;
+        }
+        (input, output)
+    }
+
+    fn model() -> NNArch {
+        let mut model =
+            NNArch::create(&[3, 5, 2], &[Activation::Tanh, Activation::Sigmoid]).with_seed(17);
+        model.randomize_range(-1.0..1.0);
+        model
+    }
+
+    fn assert_close(a: &[&NNMatrix], b: &[&NNMatrix], tol: f32) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            for (u, v) in x.data_frame.iter().zip(y.data_frame.iter()) {
+                assert!((u - v).abs() < tol, "{u} != {v}");
+            }
+        }
+    }
+
+    #[test]
+    fn batch_backprop_matches_averaged_chunks() {
+        let (input, output) = data(200);
+        let mut model = model();
+        let mut gradient = NNArch::create(&model.arch(), &model.activations);
+        model.backprop(&mut gradient, &input, &output);
+
+        // chunks of 50 rows are below the split size, so this is the serial path
+        let mut expected = NNArch::create(&model.arch(), &model.activations);
+        let mut part = NNArch::create(&model.arch(), &model.activations);
+        for start in (0..200).step_by(50) {
+            let rows = start..start + 50;
+            model.backprop(
+                &mut part,
+                &input.rows_view(rows.clone()),
+                &output.rows_view(rows),
+            );
+            for i in 0..model.layer_count {
+                expected.wl[i] += &part.wl[i] * 0.25;
+                expected.bl[i] += &part.bl[i] * 0.25;
+            }
+        }
+        assert_close(&gradient.params(), &expected.params(), 1e-6);
+    }
+
+    #[test]
+    fn batch_backprop_keeps_the_state_of_the_serial_path() {
+        let (input, output) = data(200);
+        let mut model = model();
+        let mut gradient = NNArch::create(&model.arch(), &model.activations);
+        model.backprop(&mut gradient, &input, &output);
+
+        // every chunk of 50 rows takes the serial path, the batch its rows of al and zl
+        for start in (0..200).step_by(50) {
+            let rows = start..start + 50;
+            let mut serial = model.clone();
+            let mut part = NNArch::create(&model.arch(), &model.activations);
+            serial.backprop(
+                &mut part,
+                &input.rows_view(rows.clone()),
+                &output.rows_view(rows.clone()),
+            );
+            for (batch, chunk) in [(&model, &serial), (&gradient, &part)] {
+                for (a, b) in batch.al.iter().zip(&chunk.al) {
+                    assert_eq!(a.rows_view(rows.clone()), b.view());
+                }
+                for (a, b) in batch.zl.iter().zip(&chunk.zl) {
+                    assert_eq!(a.rows_view(rows.clone()), b.view());
+                }
+            }
+        }
+        assert_eq!(model.get_output().rows, 200);
+
+        let mut numeric = NNArch::create(&model.arch(), &model.activations);
+        model.finite_diff(&mut numeric, &input, &output, 1e-3);
+        assert_close(&numeric.params(), &gradient.params(), 1e-2);
+    }
+
+    #[test]
+    fn finite_diff_matches_backprop_and_restores_the_model() {
+        let (input, output) = data(100);
+        let mut model = model();
+        let before = model.clone();
+        let mut numeric = NNArch::create(&model.arch(), &model.activations);
+        let mut analytic = NNArch::create(&model.arch(), &model.activations);
+        model.finite_diff_central(&mut numeric, &input, &output, 1e-2);
+        model.backprop(&mut analytic, &input, &output);
+        assert_close(&numeric.params(), &analytic.params(), 1e-3);
+        assert_eq!(model.params(), before.params());
+    }
+
+    #[test]
+    fn large_product_matches_small_products() {
+        let mut a = NNMatrix::empty(300, 784);
+        let mut b = NNMatrix::empty(784, 16);
+        for (i, v) in a.data_frame.iter_mut().enumerate() {
+            *v = ((i * 7) % 11) as f32 / 11.0 - 0.5;
+        }
+        for (i, v) in b.data_frame.iter_mut().enumerate() {
+            *v = ((i * 5) % 13) as f32 / 13.0 - 0.5;
+        }
+        let c = &a * &b;
+        // eight rows at a time are always below the split size
+        for i in (0..296).step_by(37) {
+            let block = a.rows_view(i..i + 8) * &b;
+            assert_eq!(block.view(), c.rows_view(i..i + 8));
+        }
+    }
+}
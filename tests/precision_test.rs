@@ -0,0 +1,61 @@
+#[cfg(test)]
+pub mod precision_tests {
+    use mm_nn::nn::{gradcheck, Activation, Initializer, Loss, NNArch, NNMatrix, T};
+
+    const XOR: [T; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+    fn model() -> NNArch {
+        let activations = [Activation::Tanh, Activation::Tanh, Activation::Sigmoid];
+        let mut model = NNArch::create(&[2, 4, 3, 1], &activations).with_seed(6);
+        model.initialize(Initializer::XavierUniform, Initializer::Constant(0.1));
+        model
+    }
+
+    #[test]
+    fn f64_gradcheck_is_much_tighter() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+        let mut model = model();
+        let mut wide: NNArch<f64> = model.cast();
+
+        let narrow = gradcheck(&mut model, &df_input, &df_output, 1e-2);
+        let report = gradcheck(&mut wide, &df_input.cast(), &df_output.cast(), 1e-6);
+        assert!(report.max_abs() < 1e-8, "{report}");
+        assert!((report.max_abs() as T) < narrow.max_abs() / 100.0);
+    }
+
+    #[test]
+    fn cast_keeps_the_model() {
+        let df_input = NNMatrix::new(Some(&XOR[..]), 4, 2, 3);
+        let df_output = NNMatrix::new(Some(&XOR[2..]), 4, 1, 3);
+        let mut model = model().with_loss(Loss::BinaryCrossEntropy);
+        let mut wide = model.cast::<f64>();
+        assert_eq!(wide.loss, model.loss);
+        assert_eq!(wide.activations, model.activations);
+        assert_eq!(wide.cast::<f32>().wl, model.wl);
+
+        let cost = model.cost(&df_input, &df_output);
+        let wide_cost = wide.cost(&df_input.cast(), &df_output.cast());
+        assert!((wide_cost - cost as f64).abs() < 1e-5);
+    }
+
+    #[test]
+    fn f64_model_file_round_trips() {
+        let mut wide: NNArch<f64> = model().cast().with_loss(Loss::Huber(0.3));
+        wide.activations[0] = Activation::LeakyRelu(0.1);
+        let bytes = wide.to_bytes();
+        let loaded = NNArch::<f64>::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.wl, wide.wl);
+        assert_eq!(loaded.bl, wide.bl);
+        // the parameters are f64 in every file so they keep every bit
+        assert_eq!(loaded.activations, wide.activations);
+        assert_eq!(loaded.loss, Loss::Huber(0.3));
+        assert_eq!(Activation::LeakyRelu(0.1).apply(-1.0), -0.1);
+
+        // loading into the other precision converts the values
+        let narrow = NNArch::<f32>::from_bytes(&bytes).unwrap();
+        assert_eq!(narrow.wl, wide.cast::<f32>().wl);
+        let json: NNArch<f64> = NNArch::from_json(&wide.to_json()).unwrap();
+        assert_eq!(json.bl, wide.bl);
+    }
+}
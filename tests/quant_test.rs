@@ -0,0 +1,126 @@
+#[cfg(test)]
+pub mod quant_tests {
+    use mm_nn::nn::{Activation, Granularity, Initializer, Loss, NNArch, NNMatrix, QuantParams, T};
+
+    fn model() -> NNArch {
+        let activations = [Activation::Relu, Activation::Relu, Activation::Softmax];
+        let mut model = NNArch::create(&[6, 32, 16, 4], &activations)
+            .with_loss(Loss::CategoricalCrossEntropy)
+            .with_seed(20);
+        model.initialize(Initializer::HeUniform, Initializer::Constant(0.05));
+        model
+    }
+
+    fn data(rows: usize) -> (NNMatrix, NNMatrix) {
+        let mut input: NNMatrix = NNMatrix::empty(rows, 6);
+        let mut output: NNMatrix = NNMatrix::empty(rows, 4);
+        for i in 0..rows {
+            for j in 0..6 {
+                input[(i, j)] = ((i * 7 + j * 3) % 17) as T / 8.0 - 1.0;
+            }
+            output[(i, i % 4)] = 1.0;
+        }
+        (input, output)
+    }
+
+    #[test]
+    fn params_map_zero_exactly_and_clamp() {
+        let p = QuantParams::from_range(-1.0, 3.0);
+        assert_eq!(p.dequantize(p.quantize(0.0)), 0.0);
+        for v in [-1.0, -0.3, 0.7, 2.99] {
+            assert!((p.dequantize(p.quantize(v)) - v).abs() <= p.scale / 2.0 + 1e-6);
+        }
+        assert_eq!(p.quantize(10.0), 127);
+        assert_eq!(p.quantize(-10.0), -128);
+
+        let s = QuantParams::symmetric(0.5);
+        assert_eq!(s.zero_point, 0);
+        assert_eq!(s.quantize(0.5), 127);
+        assert_eq!(s.quantize(-0.5), -127);
+    }
+
+    #[test]
+    fn integer_path_matches_dequantized_weights() {
+        let mut model = NNArch::create(&[3, 2], &[Activation::Identity]).with_seed(2);
+        model.randomize_range(-1.0..1.0);
+        let (data, _) = data(5);
+        let input = data.cols_view(0..3);
+        let quant = model.quantize(&input);
+        let layer = &quant.layers[0];
+
+        let output = quant.forward_batch(&input);
+        for i in 0..input.rows {
+            for j in 0..2 {
+                let w = layer.params(j);
+                let scale = layer.input_params.scale * w.scale;
+                let mut expected = layer.bias[j] as T * scale;
+                for k in 0..3 {
+                    let a = layer.input_params.quantize(input[(i, k)]);
+                    expected +=
+                        layer.input_params.dequantize(a) * w.dequantize(layer.weights[k * 2 + j]);
+                }
+                assert!((output[(i, j)] - expected).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn quantized_model_stays_close_to_float() {
+        let (input, output) = data(200);
+        let mut model = model();
+        let quant = model.quantize(&input);
+        let report = quant.evaluate(&mut model, &input, &output);
+        assert!(report.max_abs < 5e-2, "{report}");
+        assert!(report.accuracy_drop().abs() <= 0.05, "{report}");
+        assert!(report.quant_bytes * 2 < report.bytes, "{report}");
+    }
+
+    #[test]
+    fn per_channel_handles_uneven_columns() {
+        let (input, output) = data(100);
+        let mut model = model();
+        for i in 0..model.wl[0].rows {
+            model.wl[0][(i, 0)] *= 20.0;
+        }
+        let per_layer = model.quantize_with(&input, Granularity::PerLayer);
+        let per_channel = model.quantize_with(&input, Granularity::PerChannel);
+        assert_eq!(per_layer.layers[0].weight_params.len(), 1);
+        assert_eq!(per_channel.layers[0].weight_params.len(), 32);
+
+        let coarse = per_layer.evaluate(&mut model, &input, &output);
+        let fine = per_channel.evaluate(&mut model, &input, &output);
+        assert!(fine.mean_abs < coarse.mean_abs, "{fine}\n{coarse}");
+    }
+
+    #[test]
+    fn strided_weights_skip_the_padding() {
+        let (input, _) = data(20);
+        let mut model = model();
+        let contiguous = model.quantize_with(&input, Granularity::PerLayer);
+
+        // same weights with a padding column of large values after every row
+        let w = model.wl[0].clone();
+        let mut padded: NNMatrix = NNMatrix::empty(w.rows, w.cols + 1);
+        for (i, row) in w.rows().enumerate() {
+            padded.row_mut(i)[..w.cols].copy_from_slice(row);
+            padded[(i, w.cols)] = 1e3;
+        }
+        model.wl[0] = NNMatrix::new(Some(&padded.data_frame), w.rows, w.cols, w.cols + 1);
+        let strided = model.quantize_with(&input, Granularity::PerLayer);
+        assert_eq!(strided.layers[0], contiguous.layers[0]);
+    }
+
+    #[test]
+    fn cost_uses_the_fused_loss_like_the_float_model() {
+        let mut model: NNArch = NNArch::create(&[2, 2], &[Activation::Softmax])
+            .with_loss(Loss::CategoricalCrossEntropy);
+        model.wl[0] = NNMatrix::new(Some(&[50.0, -50.0, -50.0, 50.0]), 2, 2, 2);
+        let input = NNMatrix::new(Some(&[1.0, 0.0, 0.0, 1.0]), 2, 2, 2);
+        // both rows are confidently wrong, the clamped probability would cap the cost near 16
+        let output = NNMatrix::new(Some(&[0.0, 1.0, 1.0, 0.0]), 2, 2, 2);
+        let quant = model.quantize(&input);
+        let cost = model.cost(&input, &output);
+        assert!(cost > 99.0, "{cost}");
+        assert!((quant.cost(&input, &output) - cost).abs() < 1.0);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+pub mod seed_tests {
+    use mm_nn::nn::{train_test_split, Activation, Initializer, NNArch, NNMatrix, T};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn model(seed: u64) -> NNArch {
+        NNArch::create(&[3, 4, 2], &[Activation::Relu, Activation::Identity]).with_seed(seed)
+    }
+
+    #[test]
+    fn same_seed_gives_identical_weights() {
+        let mut a = model(11);
+        let mut b = model(11);
+        a.randomize();
+        b.randomize();
+        assert_eq!(a.wl, b.wl);
+        assert_eq!(a.bl, b.bl);
+
+        a.initialize(Initializer::HeNormal, Initializer::XavierUniform);
+        b.initialize(Initializer::HeNormal, Initializer::XavierUniform);
+        assert_eq!(a.wl, b.wl);
+        assert_eq!(a.bl, b.bl);
+    }
+
+    #[test]
+    fn different_seeds_give_different_weights() {
+        let mut a = model(1);
+        let mut b = model(2);
+        a.initialize(Initializer::Orthogonal, Initializer::Zeros);
+        b.initialize(Initializer::Orthogonal, Initializer::Zeros);
+        assert_ne!(a.wl, b.wl);
+    }
+
+    #[test]
+    fn explicit_rng_matches_model_seed() {
+        let mut a = model(5);
+        let mut b = model(99);
+        a.randomize_range(-1.0..1.0);
+        b.randomize_range_with(-1.0..1.0, &mut StdRng::seed_from_u64(5));
+        assert_eq!(a.wl, b.wl);
+        assert_eq!(a.bl, b.bl);
+    }
+
+    #[test]
+    fn split_is_reproducible_and_disjoint() {
+        let td: Vec<T> = (0..20).map(|v| v as T).collect();
+        let df_input = NNMatrix::new(Some(&td[..]), 10, 1, 2);
+        let df_output = NNMatrix::new(Some(&td[1..]), 10, 1, 2);
+
+        let (train_in, train_out, test_in, test_out) =
+            train_test_split(&df_input, &df_output, 0.3, &mut StdRng::seed_from_u64(3));
+        let again = train_test_split(&df_input, &df_output, 0.3, &mut StdRng::seed_from_u64(3));
+        assert_eq!(train_in, again.0);
+        assert_eq!(test_in, again.2);
+
+        assert_eq!(train_in.rows, 7);
+        assert_eq!(test_in.rows, 3);
+        // 2.9 rows round to 3 instead of being cut to 2
+        let rounded = train_test_split(&df_input, &df_output, 0.29, &mut StdRng::seed_from_u64(3));
+        assert_eq!(rounded.2.rows, 3);
+        let mut seen: Vec<T> = Vec::new();
+        for (input, output) in [(&train_in, &train_out), (&test_in, &test_out)] {
+            for i in 0..input.rows {
+                // every output still belongs to its input
+                assert_eq!(input.get_at(i, 0) + 1.0, output.get_at(i, 0));
+                seen.push(input.get_at(i, 0));
+            }
+        }
+        seen.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(seen, (0..10).map(|v| (2 * v) as T).collect::<Vec<T>>());
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+pub mod simd_tests {
+    use mm_nn::nn::simd::Level;
+    use mm_nn::nn::{simd, Activation, NNMatrix};
+
+    type Kernel = fn(Level, &mut [f32]);
+    type Reference = fn(f32) -> f32;
+
+    /// n values spread over lo..hi, n is odd so the vector loops leave a tail.
+    fn spread(lo: f32, hi: f32, n: usize) -> Vec<f32> {
+        (0..n)
+            .map(|i| lo + (hi - lo) * i as f32 / (n - 1) as f32)
+            .collect()
+    }
+
+    #[test]
+    fn exp_is_close_to_std() {
+        let xs = spread(-87.0, 88.0, 1001);
+        let mut ys = xs.clone();
+        simd::exp(&mut ys);
+        for (&x, &y) in xs.iter().zip(ys.iter()) {
+            let expected = (x as f64).exp();
+            let rel = ((y as f64 - expected) / expected).abs();
+            assert!(rel < 2e-6, "exp({x}) = {y} expected {expected}");
+            assert!(((simd::exp_approx(x) as f64 - expected) / expected).abs() < 2e-6);
+        }
+        // saturates instead of overflowing
+        let mut big = [1000.0, -1000.0];
+        simd::exp(&mut big);
+        assert!(big[0].is_finite() && big[1] >= 0.0);
+    }
+
+    #[test]
+    fn sigmoid_and_tanh_are_close_to_std() {
+        let xs = spread(-20.0, 20.0, 4001);
+        let mut s = xs.clone();
+        let mut t = xs.clone();
+        simd::sigmoid(&mut s);
+        simd::tanh(&mut t);
+        for i in 0..xs.len() {
+            let x = xs[i] as f64;
+            assert!((s[i] as f64 - 1.0 / (1.0 + (-x).exp())).abs() < 1e-6);
+            assert!((t[i] as f64 - x.tanh()).abs() < 1e-6, "tanh({x})");
+            assert!((simd::tanh_approx(xs[i]) - t[i]).abs() < 1e-6);
+        }
+        let mut tiny = [1e-4, -1e-4];
+        simd::tanh(&mut tiny);
+        assert!((tiny[0] - 1e-4).abs() < 1e-10 && tiny[1] == -tiny[0]);
+    }
+
+    #[test]
+    fn elementwise_ops_skip_the_stride_padding() {
+        // 2 x 3 matrix with a padding column of 9s
+        let mut m: NNMatrix = NNMatrix::new(Some(&[1.0, 2.0, 3.0, 9.0, 4.0, 5.0, 6.0]), 2, 3, 4);
+        m *= 2.0;
+        m += 1.0;
+        assert_eq!(m.get_row(0)[..], [3.0, 5.0, 7.0]);
+        assert_eq!(m.get_row(1)[..], [9.0, 11.0, 13.0]);
+        assert_eq!(m.data_frame[3], 9.0);
+
+        let sum = &m + m.clone();
+        assert_eq!(sum.stride, 3);
+        assert_eq!(sum.get_row(1)[..], [18.0, 22.0, 26.0]);
+        assert_eq!((&m * 0.5).get_row(0)[..], [1.5, 2.5, 3.5]);
+    }
+
+    #[test]
+    fn activations_use_the_kernels() {
+        let z = NNMatrix::new(Some(&spread(-6.0, 6.0, 33)), 3, 11, 11);
+        let mut a = NNMatrix::empty(3, 11);
+        for activation in [Activation::Sigmoid, Activation::Tanh] {
+            activation.forward(&z, &mut a);
+            for i in 0..3 {
+                for j in 0..11 {
+                    let exact = activation.apply(z[(i, j)]);
+                    assert!((a[(i, j)] - exact).abs() < 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_level_matches_the_scalar_reference() {
+        let xs = spread(-30.0, 30.0, 1001);
+        let levels = [Level::Avx2, Level::Sse41, Level::Scalar];
+        for level in levels.into_iter().filter(|&l| simd::supported(l)) {
+            let kernels: [(Kernel, Reference); 3] = [
+                (simd::exp_at, simd::exp_approx),
+                (simd::sigmoid_at, simd::sigmoid_approx),
+                (simd::tanh_at, simd::tanh_approx),
+            ];
+            for (kernel, reference) in kernels {
+                let mut ys = xs.clone();
+                kernel(level, &mut ys);
+                for (&x, &y) in xs.iter().zip(ys.iter()) {
+                    let expected = reference(x);
+                    assert!(
+                        (y - expected).abs() <= 2e-6 * expected.abs().max(1.0),
+                        "{level:?} {x}"
+                    );
+                }
+            }
+
+            let mut ys = xs.clone();
+            simd::add_at(level, &mut ys, &xs);
+            simd::mul_scalar_at(level, &mut ys, 0.5);
+            simd::add_scalar_at(level, &mut ys, 1.0);
+            assert!(ys.iter().zip(&xs).all(|(y, x)| *y == x + 1.0), "{level:?}");
+        }
+    }
+
+    #[test]
+    fn nan_passes_through_every_level() {
+        let levels = [Level::Avx2, Level::Sse41, Level::Scalar];
+        for level in levels.into_iter().filter(|&l| simd::supported(l)) {
+            let kernels: [Kernel; 3] = [simd::exp_at, simd::sigmoid_at, simd::tanh_at];
+            for kernel in kernels {
+                // nan in the vector lanes and in the scalar tail
+                let mut x = [f32::NAN; 19];
+                x[1] = 0.5;
+                kernel(level, &mut x);
+                assert!(
+                    x.iter().enumerate().all(|(i, v)| v.is_nan() != (i == 1)),
+                    "{level:?} {x:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+pub mod trainer_tests {
+    use mm_nn::nn::{
+        train_test_split, Activation, Adam, Initializer, NNArch, NNMatrix, Sgd, Trainer, T,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn data() -> (NNMatrix, NNMatrix) {
+        // y = x / 2 for 10 samples
+        let mut td: Vec<T> = Vec::new();
+        for i in 0..10 {
+            let x = i as T / 10.0;
+            td.push(x);
+            td.push(x / 2.0);
+        }
+        (
+            NNMatrix::new(Some(&td[..]), 10, 1, 2),
+            NNMatrix::new(Some(&td[1..]), 10, 1, 2),
+        )
+    }
+
+    #[test]
+    fn counts_epochs_and_steps() {
+        let (df_input, df_output) = data();
+        let activations = [Activation::Identity];
+        let mut model = NNArch::create(&[1, 1], &activations);
+        let mut gradient = NNArch::create(&[1, 1], &activations);
+        let mut trainer = Trainer::new(3).with_seed(7);
+
+        let history = trainer.train(
+            &mut model,
+            &mut gradient,
+            &mut Sgd::new(0.1),
+            &df_input,
+            &df_output,
+            5,
+        );
+        assert_eq!(history.len(), 5);
+        assert_eq!(trainer.epoch, 5);
+        assert_eq!(trainer.steps_per_epoch(10), 4);
+        assert_eq!(trainer.step, 20);
+        assert!(history[4] < history[0]);
+    }
+
+    #[test]
+    fn same_seed_gives_same_weights() {
+        let (df_input, df_output) = data();
+        let activations = [Activation::Tanh, Activation::Identity];
+        let mut base = NNArch::create(&[1, 4, 1], &activations).with_seed(7);
+        base.randomize();
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let mut model = base.clone();
+            let mut gradient = base.clone();
+            let mut trainer = Trainer::new(2).with_seed(42);
+            trainer.train(
+                &mut model,
+                &mut gradient,
+                &mut Adam::new(0.01),
+                &df_input,
+                &df_output,
+                3,
+            );
+            runs.push(model);
+        }
+        assert_eq!(runs[0].wl, runs[1].wl);
+        assert_eq!(runs[0].bl, runs[1].bl);
+    }
+
+    #[test]
+    fn one_rng_drives_init_split_and_shuffle() {
+        let (df_input, df_output) = data();
+        let activations = [Activation::Tanh, Activation::Identity];
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut model = NNArch::create(&[1, 4, 1], &activations);
+            model.initialize_with(Initializer::XavierUniform, Initializer::Zeros, &mut rng);
+            let (train_input, train_output, _, _) =
+                train_test_split(&df_input, &df_output, 0.2, &mut rng);
+            let mut gradient = model.clone();
+            Trainer::new(2).train_with(
+                &mut model,
+                &mut gradient,
+                &mut Sgd::new(0.1),
+                &train_input,
+                &train_output,
+                2,
+                &mut rng,
+            );
+            model
+        };
+        assert_eq!(run(5).wl, run(5).wl);
+        assert_ne!(run(5).wl, run(6).wl);
+    }
+
+    #[test]
+    fn seeded_trainer_shuffles_like_the_same_rng() {
+        let (df_input, df_output) = data();
+        let mut base = NNArch::create(&[1, 1], &[Activation::Identity]).with_seed(3);
+        base.randomize();
+        let (mut a, mut b) = (base.clone(), base.clone());
+        let mut gradient = base.clone();
+        Trainer::new(3).with_seed(9).train_epoch(
+            &mut a,
+            &mut gradient,
+            &mut Sgd::new(0.1),
+            &df_input,
+            &df_output,
+        );
+        Trainer::new(3).train_epoch_with(
+            &mut b,
+            &mut gradient,
+            &mut Sgd::new(0.1),
+            &df_input,
+            &df_output,
+            &mut StdRng::seed_from_u64(9),
+        );
+        assert_eq!(a.wl, b.wl);
+    }
+
+    #[test]
+    fn select_rows_gathers_in_order() {
+        let (df_input, _) = data();
+        let batch = df_input.select_rows(&[4, 0, 9]);
+        assert_eq!(batch.rows, 3);
+        assert_eq!(batch.get_row(0)[0], 0.4);
+        assert_eq!(batch.get_row(1)[0], 0.0);
+        assert_eq!(batch.get_row(2)[0], 0.9);
+    }
+}
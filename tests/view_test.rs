@@ -0,0 +1,93 @@
+#[cfg(test)]
+pub mod view_tests {
+    use mm_nn::nn::{Activation, NNArch, NNMatrix, NNMatrixView};
+
+    const XOR: [f32; 12] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+    #[test]
+    fn views_borrow_without_copying() {
+        let m: NNMatrix = NNMatrix::new(
+            Some(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]),
+            3,
+            3,
+            3,
+        );
+        let sub = m.submatrix(1..3, 1..3);
+        assert_eq!((sub.rows, sub.cols, sub.stride), (2, 2, 3));
+        assert_eq!(sub.row(0), [5.0, 6.0]);
+        assert_eq!(sub[(1, 0)], 8.0);
+        assert!(std::ptr::eq(&sub.row(1)[1], &m.data_frame[8]));
+
+        assert_eq!(
+            m.cols_view(2..3).to_matrix().data_frame[..],
+            [3.0, 6.0, 9.0]
+        );
+        assert_eq!(m.row_view(1).row(0), [4.0, 5.0, 6.0]);
+        let (top, bottom) = m.split_rows(1);
+        assert_eq!((top.rows, bottom.rows), (1, 2));
+        assert_eq!(bottom.row(1), [7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn mutable_view_writes_through() {
+        let mut m: NNMatrix = NNMatrix::empty(3, 3);
+        {
+            let mut sub = m.submatrix_mut(0..2, 1..3);
+            sub.fill(1.0);
+            sub[(1, 1)] = 5.0;
+            sub += NNMatrix::new(Some(&[1.0; 4]), 2, 2, 2).view();
+        }
+        assert_eq!(m.get_row(0)[..], [0.0, 2.0, 2.0]);
+        assert_eq!(m.get_row(1)[..], [0.0, 2.0, 6.0]);
+        assert_eq!(m.get_row(2)[..], [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn operators_accept_views() {
+        let a: NNMatrix = NNMatrix::new(Some(&[1.0, 2.0, 0.0, 3.0, 4.0, 0.0]), 2, 2, 3);
+        let view = NNMatrixView::new(&a.data_frame, 2, 2, 3);
+        let owned = view.to_matrix();
+        assert_eq!(view * view, &owned * &owned);
+        assert_eq!(&owned * view, &owned * &owned);
+        assert_eq!(view + &owned, &owned + owned.clone());
+        assert_eq!(view * 2.0, &owned * 2.0);
+        assert!(owned.try_matmul(&NNMatrix::empty(3, 1).view()).is_err());
+    }
+
+    #[test]
+    fn model_trains_on_views() {
+        let input = NNMatrixView::new(&XOR, 4, 2, 3);
+        let output = NNMatrixView::new(&XOR[2..], 4, 1, 3);
+        let owned_input = input.to_matrix();
+        let owned_output = output.to_matrix();
+
+        let mut model = NNArch::create(&[2, 2, 1], &[Activation::Sigmoid; 2]).with_seed(4);
+        model.randomize();
+        let mut by_view = NNArch::create(&model.arch(), &model.activations);
+        let mut by_copy = NNArch::create(&model.arch(), &model.activations);
+        model.backprop(&mut by_view, &input, &output);
+        model.backprop(&mut by_copy, &owned_input, &owned_output);
+        assert_eq!(by_view.wl, by_copy.wl);
+        assert_eq!(by_view.bl, by_copy.bl);
+        assert_eq!(
+            model.cost(&input, &output),
+            model.cost(&owned_input, &owned_output)
+        );
+    }
+
+    #[test]
+    fn zero_width_matrices_have_empty_rows() {
+        let m: NNMatrix = NNMatrix::empty(3, 0);
+        assert_eq!(m.view().rows().len(), 3);
+        assert!(m.rows().all(|row| row.is_empty()));
+        assert_eq!(m.iter().count(), 0);
+        assert_eq!(m.map(|v| v + 1.0), m);
+        assert_eq!(&m + &NNMatrix::empty(1, 0), m);
+
+        let mut c = m.clone();
+        assert_eq!(c.view_mut().rows_mut().count(), 3);
+        assert!(c.view_mut().row_mut(2).is_empty());
+        c -= &NNMatrix::empty(3, 0);
+        assert_eq!(c, m);
+    }
+}